@@ -0,0 +1,62 @@
+// Run with `cargo bench`. Prints the average time per operation of each loop.
+use std::hint::black_box;
+use std::time::Instant;
+use lj_algorithms::linked_list::{SinglyLinkedList, UnrolledList};
+
+fn bench<F: FnMut()>(name: &str, ops: usize, mut f: F) {
+    f();
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    println!("{:<40} {:>8.2} ns/op", name, elapsed.as_nanos() as f64 / ops as f64);
+}
+
+// Fills the list up to `depth` elements and drains it again, `rounds` times
+fn push_pop_front(mut list: SinglyLinkedList<u64>, rounds: usize, depth: u64) -> impl FnMut() {
+    move || {
+        for _ in 0..rounds {
+            for i in 0..depth {
+                list.push_front(black_box(i));
+            }
+            while let Some(elem) = list.pop_front() {
+                black_box(elem);
+            }
+        }
+    }
+}
+
+// Calls `f` `passes` times
+fn repeat<R, F: FnMut() -> R>(passes: usize, mut f: F) -> impl FnMut() {
+    move || {
+        for _ in 0..passes {
+            black_box(f());
+        }
+    }
+}
+
+fn main() {
+    let rounds = 20_000;
+    let depth = 64;
+    let ops = rounds * depth as usize * 2;
+    bench("push_front/pop_front, no pool", ops,
+        push_pop_front(SinglyLinkedList::new(), rounds, depth));
+    bench("push_front/pop_front, pool of 64", ops,
+        push_pop_front(SinglyLinkedList::with_node_pool(64), rounds, depth));
+    bench("push_front/pop_front, pool of 16", ops,
+        push_pop_front(SinglyLinkedList::with_node_pool(16), rounds, depth));
+
+    let len = 100_000;
+    let passes = 100;
+    let ops = len as usize * passes;
+    let singly: SinglyLinkedList<u64> = (0..len).collect();
+    let unrolled_16: UnrolledList<u64, 16> = (0..len).collect();
+    let unrolled_64: UnrolledList<u64, 64> = (0..len).collect();
+    bench("iterate, singly", ops, repeat(passes, || singly.iter().sum::<u64>()));
+    bench("iterate, unrolled by 16", ops, repeat(passes, || unrolled_16.iter().sum::<u64>()));
+    bench("iterate, unrolled by 64", ops, repeat(passes, || unrolled_64.iter().sum::<u64>()));
+    // the needle is missing, so every element is compared
+    let needle = black_box(u64::MAX);
+    bench("contains, singly", ops, repeat(passes, || singly.contains(&needle)));
+    bench("contains, unrolled by 16", ops, repeat(passes, || unrolled_16.contains(&needle)));
+    bench("contains, unrolled by 64", ops, repeat(passes, || unrolled_64.contains(&needle)));
+}
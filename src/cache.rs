@@ -0,0 +1,47 @@
+// Bounded key-value caches that keep their entries in the crate's linked lists, in the order
+// they would be evicted, and find them in O(1) through a `HashMap` of node handles.
+pub mod lru;
+pub mod lfu;
+
+pub use self::lru::LruCache;
+pub use self::lfu::LfuCache;
+
+// Called with each entry a cache evicts to make room, but not with removed or replaced ones
+type EvictionCallback<K, V> = Box<dyn FnMut(K, V) + Send>;
+
+// Only `get` and `get_mut` count as lookups; `peek` and `put` leave the counters alone
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    // 0 before the first lookup
+    pub fn hit_rate(&self) -> f64 {
+        match self.lookups() {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_rate() {
+        let mut stats = CacheStats::default();
+        assert_eq!(stats.hit_rate(), 0.0);
+        stats.hits = 3;
+        stats.misses = 1;
+        assert_eq!(stats.lookups(), 4);
+        assert_eq!(stats.hit_rate(), 0.75);
+    }
+}
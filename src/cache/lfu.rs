@@ -0,0 +1,436 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::iter::FusedIterator;
+use core::mem;
+use std::collections::HashMap;
+use crate::linked_list::arena::{self, ArenaList, NodeHandle};
+use super::{CacheStats, EvictionCallback};
+
+// Evicts the least frequently used entry, and among those the least recently used one.
+// All entries sit on `entries` ordered by use count from the highest to the lowest, and within
+// a count from the most to the least recently used. Entries with the same count form a run,
+// and `buckets` keeps one bucket per run, in the same order, pointing at the run's first
+// entry. A hit moves its entry to the front of the next run up, and eviction always takes the
+// back entry.
+pub struct LfuCache<K, V> {
+    entries: ArenaList<(K, V)>,
+    buckets: ArenaList<Bucket>,
+    map: HashMap<K, Location>,
+    capacity: usize,
+    stats: CacheStats,
+    on_evict: Option<EvictionCallback<K, V>>,
+}
+
+struct Bucket {
+    freq: u64,
+    first: NodeHandle,
+}
+
+#[derive(Clone, Copy)]
+struct Location {
+    bucket: NodeHandle,
+    node: NodeHandle,
+}
+
+pub struct Iter<'a, K: 'a, V: 'a> {
+    entries: arena::Iter<'a, (K, V)>,
+}
+
+// Whether the entry at `location` is the last one of its run
+fn ends_run<T>(entries: &ArenaList<T>, buckets: &ArenaList<Bucket>, location: Location) -> bool {
+    entries.next_handle(location.node) == buckets.next_handle(location.bucket).map(|next| buckets[next].first)
+}
+
+fn link_before<T>(entries: &mut ArenaList<T>, node: NodeHandle, entry: T) -> NodeHandle {
+    match entries.insert_before(node, entry) {
+        Ok(node) => node,
+        Err(_) => unreachable!(),
+    }
+}
+
+// Takes the entry at `location` out of both lists, dropping its bucket if the run empties
+fn unlink<T>(entries: &mut ArenaList<T>, buckets: &mut ArenaList<Bucket>, location: Location) -> T {
+    if buckets[location.bucket].first == location.node {
+        if ends_run(entries, buckets, location) {
+            buckets.remove(location.bucket);
+        } else {
+            buckets[location.bucket].first = entries.next_handle(location.node).unwrap();
+        }
+    }
+    entries.remove(location.node).unwrap()
+}
+
+// Counts one more use of the entry at `location` and moves it to the front of the next run up;
+// relinking issues a new handle
+fn touch<T>(entries: &mut ArenaList<T>, buckets: &mut ArenaList<Bucket>, location: Location) -> Location {
+    let freq = buckets[location.bucket].freq + 1;
+    let next = buckets.prev_handle(location.bucket).filter(|&prev| buckets[prev].freq == freq);
+    let alone = buckets[location.bucket].first == location.node && ends_run(entries, buckets, location);
+    if next.is_none() && alone {
+        // the entry is alone, so its bucket can simply count up in place
+        buckets[location.bucket].freq = freq;
+        return location;
+    }
+    let entry = unlink(entries, buckets, location);
+    let (bucket, node) = match next {
+        Some(next) => (next, link_before(entries, buckets[next].first, entry)),
+        None => {
+            // the entry starts a run of its own right before the rest of its old run
+            let node = link_before(entries, buckets[location.bucket].first, entry);
+            match buckets.insert_before(location.bucket, Bucket { freq, first: node }) {
+                Ok(bucket) => (bucket, node),
+                Err(_) => unreachable!(),
+            }
+        }
+    };
+    buckets[bucket].first = node;
+    Location { bucket, node }
+}
+
+impl<K, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Cannot create a cache with zero capacity");
+        Self {
+            entries: ArenaList::with_capacity(capacity),
+            buckets: ArenaList::new(),
+            map: HashMap::with_capacity(capacity),
+            capacity,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    pub fn set_on_evict<F>(&mut self, on_evict: F)
+    where F: FnMut(K, V) + Send + 'static
+    {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+
+    // From the most to the least frequently used, ties from the most to the least recent
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { entries: self.entries.iter() }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.buckets.clear();
+        self.map.clear();
+    }
+}
+
+// O(1) operations
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.map.contains_key(key)
+    }
+
+    // How many times the entry was put or got since it was inserted
+    pub fn frequency<Q>(&self, key: &Q) -> Option<u64>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.map.get(key).map(|location| self.buckets[location.bucket].freq)
+    }
+
+    // Neither counts as a use of the entry nor as a lookup
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let location = self.map.get(key)?;
+        self.entries.get(location.node).map(|(_, value)| value)
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let location = match self.map.get_mut(key) {
+            Some(location) => location,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        *location = touch(&mut self.entries, &mut self.buckets, *location);
+        self.entries.get_mut(location.node).map(|(_, value)| value)
+    }
+
+    // Returns the previous value of `key`, which also counts as a use. A new key evicts the
+    // least frequently used entry once the cache is full.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(location) = self.map.get_mut(&key) {
+            *location = touch(&mut self.entries, &mut self.buckets, *location);
+            let (_, old) = self.entries.get_mut(location.node).unwrap();
+            return Some(mem::replace(old, value));
+        }
+        if self.len() == self.capacity {
+            self.evict();
+        }
+        let entry = (key.clone(), value);
+        let location = match self.buckets.back_handle() {
+            Some(back) if self.buckets[back].freq == 1 => {
+                let node = link_before(&mut self.entries, self.buckets[back].first, entry);
+                self.buckets[back].first = node;
+                Location { bucket: back, node }
+            },
+            _ => {
+                let node = self.entries.push_back(entry);
+                Location { bucket: self.buckets.push_back(Bucket { freq: 1, first: node }), node }
+            }
+        };
+        self.map.insert(key, location);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let location = self.map.remove(key)?;
+        let (_, value) = unlink(&mut self.entries, &mut self.buckets, location);
+        Some(value)
+    }
+
+    // Removes the entry that would be evicted next, without calling the eviction callback
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        let location = Location { bucket: self.buckets.back_handle()?, node: self.entries.back_handle()? };
+        let (key, value) = unlink(&mut self.entries, &mut self.buckets, location);
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    fn evict(&mut self) {
+        if let Some((key, value)) = self.pop_lfu() {
+            self.stats.evictions += 1;
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+// O(n) operations
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    // Evicts the least frequently used entries until the cache fits
+    pub fn set_capacity(&mut self, capacity: usize) {
+        assert!(capacity > 0, "Cannot create a cache with zero capacity");
+        self.capacity = capacity;
+        while self.len() > capacity {
+            self.evict();
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LfuCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LfuCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for LfuCache<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.entries.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter { entries: self.entries.clone() }
+    }
+}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a + fmt::Debug> fmt::Debug for Iter<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Xoshiro256StarStar;
+    use std::sync::{Arc, Mutex};
+
+    fn keys(cache: &LfuCache<u32, u32>) -> Vec<u32> {
+        cache.iter().map(|(&key, _)| key).collect()
+    }
+
+    #[test]
+    fn evicts_least_frequently_used() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let mut cache = LfuCache::new(3);
+        let sink = evicted.clone();
+        cache.set_on_evict(move |key, value| sink.lock().unwrap().push((key, value)));
+        cache.extend((0..3).map(|key| (key, key * 10)));
+        cache.get(&0);
+        cache.get(&0);
+        cache.get(&1);
+        assert_eq!((cache.frequency(&0), cache.frequency(&1), cache.frequency(&2)), (Some(3), Some(2), Some(1)));
+        assert_eq!(keys(&cache), vec![0, 1, 2]);
+        cache.put(3, 30);
+        assert_eq!(keys(&cache), vec![0, 1, 3]);
+        // updating counts as a use, and within a count the most recent use comes first
+        assert_eq!(cache.put(3, 31), Some(30));
+        assert_eq!(keys(&cache), vec![0, 3, 1]);
+        assert_eq!(cache.put(1, 11), Some(10));
+        assert_eq!(keys(&cache), vec![1, 0, 3]);
+        cache.put(4, 40);
+        cache.put(5, 50);
+        assert_eq!(*evicted.lock().unwrap(), vec![(2, 20), (3, 31), (4, 40)]);
+        assert_eq!(format!("{:?}", cache), "{1: 11, 0: 0, 5: 50}");
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let mut cache = LfuCache::new(2);
+        cache.put("a", 1);
+        assert_eq!(cache.get("a"), Some(&1));
+        *cache.get_mut("a").unwrap() += 1;
+        assert_eq!(cache.get("b"), None);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 1, evictions: 1 });
+        assert_eq!(cache.peek("a"), Some(&2));
+        assert_eq!(cache.peek("b"), None);
+        assert_eq!(cache.frequency("a"), Some(3));
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn remove_and_resize() {
+        let mut cache = LfuCache::new(4);
+        cache.extend((0..4).map(|key| (key, key)));
+        cache.get(&0);
+        assert_eq!(cache.remove(&1), Some(1));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.pop_lfu(), Some((2, 2)));
+        cache.put(5, 5);
+        cache.set_capacity(1);
+        assert_eq!(keys(&cache), vec![0]);
+        assert_eq!(cache.stats().evictions, 2);
+        cache.clear();
+        assert!(cache.is_empty() && !cache.contains_key(&0));
+    }
+
+    // Climbing use counts keep opening and closing runs, which must not leave slots behind
+    #[test]
+    fn slots_stay_bounded() {
+        let capacity = 8;
+        let mut cache = LfuCache::new(capacity);
+        for key in 0..1000u32 {
+            cache.put(key, key);
+            for _ in 0..key % 50 {
+                cache.get(&key);
+                cache.get(&(key / 2));
+            }
+        }
+        assert!(cache.entries.capacity() <= capacity);
+        assert!(cache.buckets.capacity() <= capacity + 1);
+    }
+
+    // Checks against a `Vec` of (key, value, uses, last use) entries, evicting the one with
+    // the fewest uses and then the oldest last use
+    #[test]
+    fn matches_model() {
+        let mut rng = Xoshiro256StarStar::new(11);
+        let capacity = 8;
+        let mut cache = LfuCache::new(capacity);
+        let mut model: Vec<(u64, u64, u64, u64)> = Vec::new();
+        for step in 0..20000 {
+            let key = rng.below(16);
+            let pos = model.iter().position(|entry| entry.0 == key);
+            match rng.gen_index(4) {
+                0 | 1 => {
+                    let expected = pos.map(|pos| {
+                        let entry = &mut model[pos];
+                        entry.2 += 1;
+                        entry.3 = step;
+                        entry.1
+                    });
+                    assert_eq!(cache.get(&key).copied(), expected);
+                },
+                2 => {
+                    let old = match pos {
+                        Some(pos) => {
+                            let entry = &mut model[pos];
+                            entry.2 += 1;
+                            entry.3 = step;
+                            Some(mem::replace(&mut entry.1, step))
+                        },
+                        None => {
+                            if model.len() == capacity {
+                                let victim = (0..model.len()).min_by_key(|&i| (model[i].2, model[i].3)).unwrap();
+                                model.remove(victim);
+                            }
+                            model.push((key, step, 1, step));
+                            None
+                        }
+                    };
+                    assert_eq!(cache.put(key, step), old);
+                },
+                _ => assert_eq!(cache.remove(&key), pos.map(|pos| model.remove(pos).1)),
+            }
+            let mut expected = model.clone();
+            expected.sort_by_key(|entry| core::cmp::Reverse((entry.2, entry.3)));
+            assert!(cache.iter().map(|(&k, &v)| (k, v)).eq(expected.iter().map(|entry| (entry.0, entry.1))));
+            for entry in &model {
+                assert_eq!(cache.frequency(&entry.0), Some(entry.2));
+            }
+        }
+    }
+}
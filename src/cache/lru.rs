@@ -0,0 +1,321 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::Hash;
+use core::iter::FusedIterator;
+use core::mem;
+use std::collections::HashMap;
+use crate::linked_list::arena::{self, ArenaList, NodeHandle};
+use super::{CacheStats, EvictionCallback};
+
+// Evicts the least recently used entry. Entries sit on `order` from the most to the least
+// recently used, and a hit relinks its entry at the front.
+pub struct LruCache<K, V> {
+    order: ArenaList<(K, V)>,
+    map: HashMap<K, NodeHandle>,
+    capacity: usize,
+    stats: CacheStats,
+    on_evict: Option<EvictionCallback<K, V>>,
+}
+
+pub struct Iter<'a, K: 'a, V: 'a> {
+    order: arena::Iter<'a, (K, V)>,
+}
+
+// Moves the entry at `node` to the front; relinking issues a new handle
+fn promote<T>(order: &mut ArenaList<T>, node: NodeHandle) -> NodeHandle {
+    if order.front_handle() == Some(node) {
+        return node;
+    }
+    let entry = order.remove(node).unwrap();
+    order.push_front(entry)
+}
+
+impl<K, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Cannot create a cache with zero capacity");
+        Self {
+            order: ArenaList::with_capacity(capacity),
+            map: HashMap::with_capacity(capacity),
+            capacity,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    pub fn set_on_evict<F>(&mut self, on_evict: F)
+    where F: FnMut(K, V) + Send + 'static
+    {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+
+    // From the most to the least recently used
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { order: self.order.iter() }
+    }
+
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.map.clear();
+    }
+}
+
+// O(1) operations
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.map.contains_key(key)
+    }
+
+    // Neither marks the entry as used nor counts as a lookup
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let node = *self.map.get(key)?;
+        self.order.get(node).map(|(_, value)| value)
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let node = match self.map.get_mut(key) {
+            Some(node) => node,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        *node = promote(&mut self.order, *node);
+        self.order.get_mut(*node).map(|(_, value)| value)
+    }
+
+    // Returns the previous value of `key`, which also marks the entry as used. A new key
+    // evicts the least recently used entry once the cache is full.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(node) = self.map.get_mut(&key) {
+            *node = promote(&mut self.order, *node);
+            let (_, old) = self.order.get_mut(*node).unwrap();
+            return Some(mem::replace(old, value));
+        }
+        if self.len() == self.capacity {
+            self.evict();
+        }
+        let node = self.order.push_front((key.clone(), value));
+        self.map.insert(key, node);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        let node = self.map.remove(key)?;
+        self.order.remove(node).map(|(_, value)| value)
+    }
+
+    // Removes the entry that would be evicted next, without calling the eviction callback
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.order.pop_back()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    fn evict(&mut self) {
+        if let Some((key, value)) = self.pop_lru() {
+            self.stats.evictions += 1;
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+// O(n) operations
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    // Evicts the least recently used entries until the cache fits
+    pub fn set_capacity(&mut self, capacity: usize) {
+        assert!(capacity > 0, "Cannot create a cache with zero capacity");
+        self.capacity = capacity;
+        while self.len() > capacity {
+            self.evict();
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for LruCache<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.order.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter { order: self.order.clone() }
+    }
+}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a + fmt::Debug> fmt::Debug for Iter<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Xoshiro256StarStar;
+    use std::sync::{Arc, Mutex};
+
+    fn keys(cache: &LruCache<u32, u32>) -> Vec<u32> {
+        cache.iter().map(|(&key, _)| key).collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let mut cache = LruCache::new(3);
+        let sink = evicted.clone();
+        cache.set_on_evict(move |key, value| sink.lock().unwrap().push((key, value)));
+        for key in 0..3 {
+            assert_eq!(cache.put(key, key * 10), None);
+        }
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.put(3, 30), None);
+        assert_eq!(keys(&cache), vec![3, 0, 2]);
+        assert_eq!(cache.put(2, 21), Some(20));
+        assert_eq!(cache.peek(&0), Some(&0));
+        cache.put(4, 40);
+        assert_eq!(keys(&cache), vec![4, 2, 3]);
+        assert_eq!(*evicted.lock().unwrap(), vec![(1, 10), (0, 0)]);
+        assert_eq!(format!("{:?}", cache), "{4: 40, 2: 21, 3: 30}");
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        assert_eq!(cache.get("a"), Some(&1));
+        *cache.get_mut("a").unwrap() += 1;
+        assert_eq!(cache.get("b"), None);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 1, evictions: 1 });
+        assert_eq!(cache.peek("a"), None);
+        assert_eq!(cache.stats().lookups(), 3);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn remove_and_resize() {
+        let mut cache = LruCache::new(4);
+        cache.extend((0..4).map(|key| (key, key)));
+        assert_eq!(cache.remove(&1), Some(1));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.pop_lru(), Some((0, 0)));
+        cache.put(5, 5);
+        cache.set_capacity(1);
+        assert_eq!(keys(&cache), vec![5]);
+        assert_eq!(cache.stats().evictions, 2);
+        cache.clear();
+        assert!(cache.is_empty() && !cache.contains_key(&5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot create a cache with zero capacity")]
+    fn zero_capacity() {
+        LruCache::<u32, u32>::new(0);
+    }
+
+    // Checks against a `Vec` of entries ordered from the most to the least recently used
+    #[test]
+    fn matches_model() {
+        let mut rng = Xoshiro256StarStar::new(7);
+        let capacity = 8;
+        let mut cache = LruCache::new(capacity);
+        let mut model: Vec<(u64, u64)> = Vec::new();
+        for step in 0..20000 {
+            let key = rng.below(16);
+            let pos = model.iter().position(|&(k, _)| k == key);
+            match rng.gen_index(4) {
+                0 | 1 => {
+                    let expected = pos.map(|pos| {
+                        let entry = model.remove(pos);
+                        model.insert(0, entry);
+                        entry.1
+                    });
+                    assert_eq!(cache.get(&key).copied(), expected);
+                },
+                2 => {
+                    let old = pos.map(|pos| model.remove(pos).1);
+                    if old.is_none() && model.len() == capacity {
+                        model.pop();
+                    }
+                    model.insert(0, (key, step));
+                    assert_eq!(cache.put(key, step), old);
+                },
+                _ => assert_eq!(cache.remove(&key), pos.map(|pos| model.remove(pos).1)),
+            }
+            assert!(cache.iter().map(|(&k, &v)| (k, v)).eq(model.iter().cloned()));
+        }
+    }
+}
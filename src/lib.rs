@@ -1,2 +1,5 @@
 pub mod sort;
 pub mod linked_list;
+pub mod strings;
+pub mod random;
+pub mod cache;
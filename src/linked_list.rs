@@ -1,12 +1,352 @@
+use core::iter::FromIterator;
+use std::collections::VecDeque;
+
 #[macro_use]
 mod testing;
 
 pub mod singly;
-mod singly_db; 
-// pub mod circular; // bugs
+pub mod singly_db;
+pub mod circular;
+pub mod doubly;
+pub mod persistent;
+pub mod arena;
+pub mod intrusive;
+pub mod skip;
+pub mod unrolled;
+pub mod xor;
+pub mod treiber;
+pub mod michael_scott;
+mod hazard;
 
 pub use self::singly::SinglyLinkedList;
-// pub use self::circular::CircularLinkedList;
+pub use self::circular::CircularLinkedList;
+pub use self::singly_db::SinglyLinkedListDb;
+pub use self::doubly::DoublyLinkedList;
+pub use self::persistent::{PersistentList, ArcPersistentList};
+pub use self::arena::{ArenaList, NodeHandle};
+pub use self::skip::{SkipList, SkipSet};
+pub use self::unrolled::UnrolledList;
+pub use self::xor::XorLinkedList;
+pub use self::treiber::ConcurrentStack;
+pub use self::michael_scott::ConcurrentQueue;
 
 test_one!(singly_list_tests, SinglyLinkedList);
-// test_one!(circular_list_tests, CircularLinkedList);
+test_one!(circular_list_tests, CircularLinkedList);
+test_one!(doubly_list_tests, DoublyLinkedList);
+test_one!(arena_list_tests, ArenaList);
+test_one!(unrolled_list_tests, UnrolledList);
+test_one!(xor_list_tests, XorLinkedList);
+
+// Operations shared by every list type. Complexity varies: e.g. `pop_back` is O(n) on
+// `SinglyLinkedList` and `push_front` is O(n) on `Vec`.
+pub trait List<T>: Default + Extend<T> + FromIterator<T> + IntoIterator<Item = T> {
+    type Iter<'a>: Iterator<Item = &'a T> where Self: 'a, T: 'a;
+    type IterMut<'a>: Iterator<Item = &'a mut T> where Self: 'a, T: 'a;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter(&self) -> Self::Iter<'_>;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+
+    fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        self.iter().any(|elem| elem == value)
+    }
+
+    fn clear(&mut self);
+
+    fn front(&self) -> Option<&T>;
+
+    fn front_mut(&mut self) -> Option<&mut T>;
+
+    fn back(&self) -> Option<&T>;
+
+    fn back_mut(&mut self) -> Option<&mut T>;
+
+    fn push_front(&mut self, elem: T);
+
+    fn push_back(&mut self, elem: T);
+
+    fn pop_front(&mut self) -> Option<T>;
+
+    fn pop_back(&mut self) -> Option<T>;
+
+    fn split_off(&mut self, at: usize) -> Self;
+
+    fn append(&mut self, other: &mut Self);
+}
+
+// Last in, first out, every operation O(1)
+pub trait Stack<T> {
+    fn push(&mut self, elem: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+}
+
+// First in, first out, every operation O(1)
+pub trait Queue<T> {
+    fn enqueue(&mut self, elem: T);
+
+    fn dequeue(&mut self) -> Option<T>;
+
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+}
+
+// Lists whose push, pop and access at both ends are all O(1)
+pub trait Deque<T>: List<T> {}
+
+macro_rules! impl_list {
+    ($ll_name: ident, $module: ident) => {
+impl<T> List<T> for $ll_name<T> {
+    type Iter<'a> = $module::Iter<'a, T> where T: 'a;
+    type IterMut<'a> = $module::IterMut<'a, T> where T: 'a;
+
+    fn len(&self) -> usize { $ll_name::len(self) }
+    fn is_empty(&self) -> bool { $ll_name::is_empty(self) }
+    fn iter(&self) -> Self::Iter<'_> { $ll_name::iter(self) }
+    fn iter_mut(&mut self) -> Self::IterMut<'_> { $ll_name::iter_mut(self) }
+    fn contains(&self, value: &T) -> bool where T: PartialEq<T> { $ll_name::contains(self, value) }
+    fn clear(&mut self) { $ll_name::clear(self) }
+    fn front(&self) -> Option<&T> { $ll_name::front(self) }
+    fn front_mut(&mut self) -> Option<&mut T> { $ll_name::front_mut(self) }
+    fn back(&self) -> Option<&T> { $ll_name::back(self) }
+    fn back_mut(&mut self) -> Option<&mut T> { $ll_name::back_mut(self) }
+    fn push_front(&mut self, elem: T) { $ll_name::push_front(self, elem); }
+    fn push_back(&mut self, elem: T) { $ll_name::push_back(self, elem); }
+    fn pop_front(&mut self) -> Option<T> { $ll_name::pop_front(self) }
+    fn pop_back(&mut self) -> Option<T> { $ll_name::pop_back(self) }
+    fn split_off(&mut self, at: usize) -> Self { $ll_name::split_off(self, at) }
+    fn append(&mut self, other: &mut Self) { $ll_name::append(self, other) }
+}
+
+impl<T> Stack<T> for $ll_name<T> {
+    fn push(&mut self, elem: T) { self.push_front(elem); }
+    fn pop(&mut self) -> Option<T> { self.pop_front() }
+    fn peek(&self) -> Option<&T> { self.front() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.front_mut() }
+}
+
+impl<T> Queue<T> for $ll_name<T> {
+    fn enqueue(&mut self, elem: T) { self.push_back(elem); }
+    fn dequeue(&mut self) -> Option<T> { self.pop_front() }
+    fn peek(&self) -> Option<&T> { self.front() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.front_mut() }
+}
+    };
+}
+
+impl_list!(SinglyLinkedList, singly);
+impl_list!(CircularLinkedList, circular);
+impl_list!(DoublyLinkedList, doubly);
+impl_list!(ArenaList, arena);
+impl_list!(XorLinkedList, xor);
+
+// By hand rather than with `impl_list!`, to cover every block size
+impl<T, const B: usize> List<T> for UnrolledList<T, B> {
+    type Iter<'a> = unrolled::Iter<'a, T, B> where T: 'a;
+    type IterMut<'a> = unrolled::IterMut<'a, T, B> where T: 'a;
+
+    fn len(&self) -> usize { UnrolledList::len(self) }
+    fn is_empty(&self) -> bool { UnrolledList::is_empty(self) }
+    fn iter(&self) -> Self::Iter<'_> { UnrolledList::iter(self) }
+    fn iter_mut(&mut self) -> Self::IterMut<'_> { UnrolledList::iter_mut(self) }
+    fn contains(&self, value: &T) -> bool where T: PartialEq<T> { UnrolledList::contains(self, value) }
+    fn clear(&mut self) { UnrolledList::clear(self) }
+    fn front(&self) -> Option<&T> { UnrolledList::front(self) }
+    fn front_mut(&mut self) -> Option<&mut T> { UnrolledList::front_mut(self) }
+    fn back(&self) -> Option<&T> { UnrolledList::back(self) }
+    fn back_mut(&mut self) -> Option<&mut T> { UnrolledList::back_mut(self) }
+    fn push_front(&mut self, elem: T) { UnrolledList::push_front(self, elem); }
+    fn push_back(&mut self, elem: T) { UnrolledList::push_back(self, elem); }
+    fn pop_front(&mut self) -> Option<T> { UnrolledList::pop_front(self) }
+    fn pop_back(&mut self) -> Option<T> { UnrolledList::pop_back(self) }
+    fn split_off(&mut self, at: usize) -> Self { UnrolledList::split_off(self, at) }
+    fn append(&mut self, other: &mut Self) { UnrolledList::append(self, other) }
+}
+
+impl<T, const B: usize> Stack<T> for UnrolledList<T, B> {
+    fn push(&mut self, elem: T) { self.push_front(elem); }
+    fn pop(&mut self) -> Option<T> { self.pop_front() }
+    fn peek(&self) -> Option<&T> { self.front() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.front_mut() }
+}
+
+impl<T, const B: usize> Queue<T> for UnrolledList<T, B> {
+    fn enqueue(&mut self, elem: T) { self.push_back(elem); }
+    fn dequeue(&mut self) -> Option<T> { self.pop_front() }
+    fn peek(&self) -> Option<&T> { self.front() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.front_mut() }
+}
+
+impl<T> Deque<T> for DoublyLinkedList<T> {}
+
+impl<T> Deque<T> for ArenaList<T> {}
+
+impl<T> Deque<T> for XorLinkedList<T> {}
+
+impl<T> List<T> for Vec<T> {
+    type Iter<'a> = core::slice::Iter<'a, T> where T: 'a;
+    type IterMut<'a> = core::slice::IterMut<'a, T> where T: 'a;
+
+    fn len(&self) -> usize { Vec::len(self) }
+    fn iter(&self) -> Self::Iter<'_> { <[T]>::iter(self) }
+    fn iter_mut(&mut self) -> Self::IterMut<'_> { <[T]>::iter_mut(self) }
+    fn clear(&mut self) { Vec::clear(self) }
+    fn front(&self) -> Option<&T> { self.first() }
+    fn front_mut(&mut self) -> Option<&mut T> { self.first_mut() }
+    fn back(&self) -> Option<&T> { self.last() }
+    fn back_mut(&mut self) -> Option<&mut T> { self.last_mut() }
+    fn push_front(&mut self, elem: T) { self.insert(0, elem) }
+    fn push_back(&mut self, elem: T) { self.push(elem) }
+    fn pop_front(&mut self) -> Option<T> { if self.is_empty() { None } else { Some(self.remove(0)) } }
+    fn pop_back(&mut self) -> Option<T> { self.pop() }
+    fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "Cannot split off a nonexistent index");
+        Vec::split_off(self, at)
+    }
+    fn append(&mut self, other: &mut Self) { Vec::append(self, other) }
+}
+
+impl<T> Stack<T> for Vec<T> {
+    fn push(&mut self, elem: T) { Vec::push(self, elem) }
+    fn pop(&mut self) -> Option<T> { Vec::pop(self) }
+    fn peek(&self) -> Option<&T> { self.last() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.last_mut() }
+}
+
+impl<T> List<T> for VecDeque<T> {
+    type Iter<'a> = std::collections::vec_deque::Iter<'a, T> where T: 'a;
+    type IterMut<'a> = std::collections::vec_deque::IterMut<'a, T> where T: 'a;
+
+    fn len(&self) -> usize { VecDeque::len(self) }
+    fn iter(&self) -> Self::Iter<'_> { VecDeque::iter(self) }
+    fn iter_mut(&mut self) -> Self::IterMut<'_> { VecDeque::iter_mut(self) }
+    fn clear(&mut self) { VecDeque::clear(self) }
+    fn front(&self) -> Option<&T> { VecDeque::front(self) }
+    fn front_mut(&mut self) -> Option<&mut T> { VecDeque::front_mut(self) }
+    fn back(&self) -> Option<&T> { VecDeque::back(self) }
+    fn back_mut(&mut self) -> Option<&mut T> { VecDeque::back_mut(self) }
+    fn push_front(&mut self, elem: T) { VecDeque::push_front(self, elem) }
+    fn push_back(&mut self, elem: T) { VecDeque::push_back(self, elem) }
+    fn pop_front(&mut self) -> Option<T> { VecDeque::pop_front(self) }
+    fn pop_back(&mut self) -> Option<T> { VecDeque::pop_back(self) }
+    fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "Cannot split off a nonexistent index");
+        VecDeque::split_off(self, at)
+    }
+    fn append(&mut self, other: &mut Self) { VecDeque::append(self, other) }
+}
+
+impl<T> Stack<T> for VecDeque<T> {
+    fn push(&mut self, elem: T) { self.push_back(elem) }
+    fn pop(&mut self) -> Option<T> { self.pop_back() }
+    fn peek(&self) -> Option<&T> { self.back() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.back_mut() }
+}
+
+impl<T> Queue<T> for VecDeque<T> {
+    fn enqueue(&mut self, elem: T) { self.push_back(elem) }
+    fn dequeue(&mut self) -> Option<T> { self.pop_front() }
+    fn peek(&self) -> Option<&T> { self.front() }
+    fn peek_mut(&mut self) -> Option<&mut T> { self.front_mut() }
+}
+
+impl<T> Deque<T> for VecDeque<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise_list<L: List<u32>>() {
+        let mut list = L::default();
+        assert!(list.is_empty());
+        list.push_back(2);
+        list.push_front(1);
+        list.extend(vec![3, 4]);
+        assert_eq!(list.len(), 4);
+        assert!(list.contains(&3));
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        assert_eq!((list.front(), list.back()), (Some(&10), Some(&40)));
+        *list.front_mut().unwrap() += 1;
+        *list.back_mut().unwrap() += 1;
+        let mut second = list.split_off(2);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![11, 20]);
+        assert_eq!(second.pop_back(), Some(41));
+        list.append(&mut second);
+        assert!(second.is_empty());
+        assert_eq!(list.pop_front(), Some(11));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![20, 30]);
+        let mut list: L = (0..3).collect();
+        list.clear();
+        assert_eq!(list.pop_back(), None);
+    }
+
+    fn exercise_stack<S: Stack<u32> + Default>() {
+        let mut stack = S::default();
+        stack.push(1);
+        stack.push(2);
+        *stack.peek_mut().unwrap() += 10;
+        assert_eq!(stack.peek(), Some(&12));
+        assert_eq!(stack.pop(), Some(12));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    fn exercise_queue<Q: Queue<u32> + Default>() {
+        let mut queue = Q::default();
+        queue.enqueue(1);
+        queue.enqueue(2);
+        *queue.peek_mut().unwrap() += 10;
+        assert_eq!(queue.peek(), Some(&11));
+        assert_eq!(queue.dequeue(), Some(11));
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    fn exercise_deque<D: Deque<u32>>() {
+        let mut deque: D = (1..=3).collect();
+        assert_eq!((deque.pop_front(), deque.pop_back()), (Some(1), Some(3)));
+    }
+
+    #[test]
+    fn generic_over_lists() {
+        exercise_list::<SinglyLinkedList<u32>>();
+        exercise_list::<CircularLinkedList<u32>>();
+        exercise_list::<DoublyLinkedList<u32>>();
+        exercise_list::<ArenaList<u32>>();
+        exercise_list::<UnrolledList<u32>>();
+        exercise_list::<UnrolledList<u32, 3>>();
+        exercise_list::<XorLinkedList<u32>>();
+        exercise_list::<Vec<u32>>();
+        exercise_list::<VecDeque<u32>>();
+        exercise_stack::<SinglyLinkedList<u32>>();
+        exercise_stack::<CircularLinkedList<u32>>();
+        exercise_stack::<DoublyLinkedList<u32>>();
+        exercise_stack::<Vec<u32>>();
+        exercise_stack::<VecDeque<u32>>();
+        exercise_queue::<SinglyLinkedList<u32>>();
+        exercise_queue::<CircularLinkedList<u32>>();
+        exercise_queue::<DoublyLinkedList<u32>>();
+        exercise_queue::<UnrolledList<u32>>();
+        exercise_queue::<UnrolledList<u32, 3>>();
+        exercise_queue::<VecDeque<u32>>();
+        exercise_deque::<DoublyLinkedList<u32>>();
+        exercise_deque::<ArenaList<u32>>();
+        exercise_deque::<XorLinkedList<u32>>();
+        exercise_deque::<VecDeque<u32>>();
+    }
+}
@@ -0,0 +1,656 @@
+use core::cmp::Ordering;
+use core::hash::{Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Index, IndexMut};
+
+// A linked list whose nodes live in one `Vec` slab and link to each other by index.
+// Removed slots are reused, and every reuse bumps the slot's generation so that
+// handles to removed elements are recognized as stale instead of aliasing new ones.
+// Handles are only meaningful for the list that issued them.
+pub struct ArenaList<T> {
+    slots: Vec<Slot<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    free: Option<usize>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u64,
+    entry: Entry<T>,
+}
+
+enum Entry<T> {
+    Occupied(Node<T>),
+    Vacant { next_free: Option<usize> },
+}
+
+struct Node<T> {
+    elem: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NodeHandle {
+    index: usize,
+    generation: u64,
+}
+
+pub struct Iter<'a, T: 'a> {
+    list: &'a ArenaList<T>,
+    cur: Option<usize>,
+    remaining: usize,
+}
+
+pub struct IterMut<'a, T: 'a> {
+    slots: *mut Slot<T>,
+    cur: Option<usize>,
+    remaining: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+#[derive(Clone)]
+pub struct IntoIter<T> {
+    list: ArenaList<T>
+}
+
+impl<T> ArenaList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            head: None,
+            tail: None,
+            free: None,
+            len: 0,
+        }
+    }
+
+    // Slots currently allocated, whether occupied or waiting for reuse
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        match &self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        match &mut self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    fn handle(&self, index: usize) -> NodeHandle {
+        NodeHandle { index, generation: self.slots[index].generation }
+    }
+
+    fn resolve(&self, handle: NodeHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.index)?;
+        match slot.entry {
+            Entry::Occupied(_) if slot.generation == handle.generation => Some(handle.index),
+            _ => None,
+        }
+    }
+
+    // Links a new node right after `prev`, or at the front when `prev` is `None`
+    fn link_after(&mut self, prev: Option<usize>, elem: T) -> usize {
+        let next = match prev {
+            Some(prev) => self.node(prev).next,
+            None => self.head,
+        };
+        let node = Node { elem, prev, next };
+        let index = match self.free {
+            Some(index) => {
+                if let Entry::Vacant { next_free } = self.slots[index].entry {
+                    self.free = next_free;
+                }
+                self.slots[index].entry = Entry::Occupied(node);
+                index
+            },
+            None => {
+                self.slots.push(Slot { generation: 0, entry: Entry::Occupied(node) });
+                self.slots.len() - 1
+            }
+        };
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(index),
+            None => self.head = Some(index),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        self.len += 1;
+        index
+    }
+
+    fn unlink(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        slot.generation += 1;
+        let node = match mem::replace(&mut slot.entry, Entry::Vacant { next_free: self.free }) {
+            Entry::Occupied(node) => node,
+            Entry::Vacant { .. } => unreachable!(),
+        };
+        self.free = Some(index);
+        match node.prev {
+            Some(prev) => self.node_mut(prev).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.node_mut(next).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.elem
+    }
+}
+
+// O(1) operations
+impl<T> ArenaList<T> {
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|index| &self.node(index).elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(move |index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|index| &self.node(index).elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(move |index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn front_handle(&self) -> Option<NodeHandle> {
+        self.head.map(|index| self.handle(index))
+    }
+
+    pub fn back_handle(&self) -> Option<NodeHandle> {
+        self.tail.map(|index| self.handle(index))
+    }
+
+    pub fn push_front(&mut self, elem: T) -> NodeHandle {
+        let index = self.link_after(None, elem);
+        self.handle(index)
+    }
+
+    pub fn push_back(&mut self, elem: T) -> NodeHandle {
+        let index = self.link_after(self.tail, elem);
+        self.handle(index)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|index| self.unlink(index))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|index| self.unlink(index))
+    }
+
+    // `None` once the element behind `handle` has been removed
+    pub fn get(&self, handle: NodeHandle) -> Option<&T> {
+        self.resolve(handle).map(|index| &self.node(index).elem)
+    }
+
+    pub fn get_mut(&mut self, handle: NodeHandle) -> Option<&mut T> {
+        self.resolve(handle).map(move |index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn next_handle(&self, handle: NodeHandle) -> Option<NodeHandle> {
+        let index = self.resolve(handle)?;
+        self.node(index).next.map(|next| self.handle(next))
+    }
+
+    pub fn prev_handle(&self, handle: NodeHandle) -> Option<NodeHandle> {
+        let index = self.resolve(handle)?;
+        self.node(index).prev.map(|prev| self.handle(prev))
+    }
+
+    pub fn remove(&mut self, handle: NodeHandle) -> Option<T> {
+        self.resolve(handle).map(|index| self.unlink(index))
+    }
+
+    // Hands `elem` back if `handle` is stale
+    pub fn insert_after(&mut self, handle: NodeHandle, elem: T) -> Result<NodeHandle, T> {
+        match self.resolve(handle) {
+            Some(index) => {
+                let index = self.link_after(Some(index), elem);
+                Ok(self.handle(index))
+            },
+            None => Err(elem),
+        }
+    }
+
+    pub fn insert_before(&mut self, handle: NodeHandle, elem: T) -> Result<NodeHandle, T> {
+        match self.resolve(handle) {
+            Some(index) => {
+                let index = self.link_after(self.node(index).prev, elem);
+                Ok(self.handle(index))
+            },
+            None => Err(elem),
+        }
+    }
+}
+
+// O(n) operations
+impl<T> ArenaList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { list: self, cur: self.head, remaining: self.len }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.as_mut_ptr(),
+            cur: self.head,
+            remaining: self.len,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        self.iter().any(|elem| elem == value)
+    }
+
+    // Keeps the slab, so handles issued before stay stale rather than being reissued
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // Moved elements get new handles in the returned list
+    pub fn split_off(&mut self, at: usize) -> ArenaList<T> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
+        let mut second_part = ArenaList::with_capacity(self.len - at);
+        for _ in at..self.len {
+            second_part.push_front(self.pop_back().unwrap());
+        }
+        second_part
+    }
+
+    // Moved elements get new handles in this list
+    pub fn append(&mut self, other: &mut Self) {
+        while let Some(elem) = other.pop_front() {
+            self.push_back(elem);
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        let mut cur = self.head;
+        while let Some(index) = cur {
+            let node = self.node_mut(index);
+            mem::swap(&mut node.prev, &mut node.next);
+            cur = node.prev;
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where F: FnMut(&T) -> bool
+    {
+        let mut cur = self.head;
+        while let Some(index) = cur {
+            let node = self.node(index);
+            cur = node.next;
+            if !f(&node.elem) {
+                self.unlink(index);
+            }
+        }
+    }
+}
+
+impl<T> IntoIterator for ArenaList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Hash> Hash for ArenaList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArenaList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for ArenaList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T> Index<NodeHandle> for ArenaList<T> {
+    type Output = T;
+
+    fn index(&self, handle: NodeHandle) -> &T {
+        self.get(handle).expect("Cannot access a stale handle")
+    }
+}
+
+impl<T> IndexMut<NodeHandle> for ArenaList<T> {
+    fn index_mut(&mut self, handle: NodeHandle) -> &mut T {
+        self.get_mut(handle).expect("Cannot access a stale handle")
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// Clones the slab as is, so handles issued by `self` are also valid for the clone
+impl<T: Clone> Clone for ArenaList<T> {
+    fn clone(&self) -> Self {
+        let slots = self.slots.iter().map(|slot| Slot {
+            generation: slot.generation,
+            entry: match &slot.entry {
+                Entry::Occupied(node) => Entry::Occupied(Node {
+                    elem: node.elem.clone(),
+                    prev: node.prev,
+                    next: node.next,
+                }),
+                Entry::Vacant { next_free } => Entry::Vacant { next_free: *next_free },
+            },
+        }).collect();
+        Self { slots, head: self.head, tail: self.tail, free: self.free, len: self.len }
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.cur.map(|index| {
+            let node = self.list.node(index);
+            self.cur = node.next;
+            self.remaining -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { list: self.list, cur: self.cur, remaining: self.remaining }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").finish()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.cur.map(|index| {
+            // every index is visited once, so the references never overlap
+            let node = match unsafe { &mut (*self.slots.add(index)).entry } {
+                Entry::Occupied(node) => node,
+                Entry::Vacant { .. } => unreachable!(),
+            };
+            self.cur = node.next;
+            self.remaining -= 1;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for IterMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IterMut").finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+impl<T> ArenaList<T> {
+    fn check_invariants(&self) {
+        let mut prev = None;
+        let mut cur = self.head;
+        let mut len = 0;
+        while let Some(index) = cur {
+            let node = self.node(index);
+            assert_eq!(node.prev, prev);
+            prev = cur;
+            cur = node.next;
+            len += 1;
+        }
+        assert_eq!(self.tail, prev);
+        assert_eq!(self.len, len);
+        let mut free = 0;
+        let mut cur = self.free;
+        while let Some(index) = cur {
+            match self.slots[index].entry {
+                Entry::Vacant { next_free } => cur = next_free,
+                Entry::Occupied(_) => panic!("occupied slot {} on the free list", index),
+            }
+            free += 1;
+        }
+        assert_eq!(len + free, self.slots.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Xoshiro256StarStar;
+
+    fn to_vec(list: &ArenaList<u32>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn handles_survive_other_edits() {
+        let mut list = ArenaList::new();
+        let a = list.push_back(1);
+        let b = list.push_back(2);
+        let c = list.push_front(0);
+        assert_eq!(list.insert_after(a, 10), Ok(list.next_handle(a).unwrap()));
+        assert_eq!(list.remove(b), Some(2));
+        assert_eq!(list.remove(b), None);
+        assert_eq!(list.get(b), None);
+        // the freed slot is reused, but the old handle stays stale
+        let d = list.insert_before(c, 5).unwrap();
+        assert_eq!(d.index, b.index);
+        assert_eq!(list.get(b), None);
+        assert_eq!(to_vec(&list), vec![5, 0, 1, 10]);
+        assert_eq!((list[a], list[c], list[d]), (1, 0, 5));
+        list[a] += 100;
+        let e = list.push_back(7);
+        assert_eq!(list.insert_after(b, 8), Err(8));
+        assert_eq!(list.prev_handle(a), Some(c));
+        assert_eq!((list.front_handle(), list.back_handle()), (Some(d), Some(e)));
+        assert_eq!(to_vec(&list), vec![5, 0, 101, 10, 7]);
+        let cloned = list.clone();
+        assert_eq!(cloned[e], 7);
+        list.clear();
+        assert_eq!(list.get(a), None);
+        assert_eq!(list.capacity(), 5);
+        list.check_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot access a stale handle")]
+    fn index_stale_handle() {
+        let mut list = ArenaList::new();
+        let a = list.push_back(1);
+        list.pop_back();
+        let _ = list[a];
+    }
+
+    #[test]
+    fn reverse_and_retain() {
+        let mut list: ArenaList<u32> = (0..10).collect();
+        list.reverse();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), (0..10).rev().collect::<Vec<_>>());
+        list.retain(|&x| x % 3 != 0);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![8, 7, 5, 4, 2, 1]);
+        assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), vec![1, 2, 4, 5, 7, 8]);
+    }
+
+    #[test]
+    fn invariants_under_random_ops() {
+        let mut rng = Xoshiro256StarStar::new(44);
+        let mut list = ArenaList::new();
+        let mut model = Vec::new();
+        let mut handles: Vec<NodeHandle> = Vec::new();
+        for step in 0..5000 {
+            match rng.below(5) {
+                0 => { handles.push(list.push_back(step)); model.push(step); },
+                1 => { handles.push(list.push_front(step)); model.insert(0, step); },
+                2 if !handles.is_empty() => {
+                    let handle = handles.swap_remove(rng.gen_index(handles.len()));
+                    let elem = list.remove(handle).unwrap();
+                    model.retain(|&x| x != elem);
+                },
+                3 if !handles.is_empty() => {
+                    let handle = handles[rng.gen_index(handles.len())];
+                    let at = model.iter().position(|&x| x == list[handle]).unwrap();
+                    handles.push(list.insert_after(handle, step).unwrap());
+                    model.insert(at + 1, step);
+                },
+                _ => {
+                    if let Some(elem) = list.pop_front() {
+                        model.remove(0);
+                        handles.retain(|&handle| list.get(handle).is_some());
+                        assert!(!model.contains(&elem));
+                    }
+                }
+            }
+            list.check_invariants();
+        }
+        assert_eq!(to_vec(&list), model);
+    }
+}
@@ -9,6 +9,7 @@ use core::iter::FromIterator;
 
 pub struct CircularLinkedList<T> {
     tail: Option<NonNull<Node<T>>>,
+    len: usize,
     _marker: PhantomData<Box<Node<T>>>
 }
 
@@ -17,26 +18,41 @@ struct Node<T> {
     next: Option<NonNull<Node<T>>>,
 }
 
+#[derive(Clone)]
 pub struct Iter<'a, T: 'a> {
     cur: Option<NonNull<Node<T>>>,
-    tail: Option<NonNull<Node<T>>>,
+    remaining: usize,
     _marker: PhantomData<&'a Node<T>>
 }
 
 pub struct IterMut<'a, T: 'a> {
     cur: Option<NonNull<Node<T>>>,
-    tail: Option<NonNull<Node<T>>>,
+    remaining: usize,
     _marker: PhantomData<&'a mut Node<T>>,
 }
 
+#[derive(Clone)]
 pub struct IntoIter<T> {
     list: CircularLinkedList<T> 
 }
 
+// Cursors never reach an end: moving past the tail wraps around to the head.
+// They keep the node before the current one so that the current one can be unlinked.
+pub struct Cursor<'a, T: 'a> {
+    prev: Option<NonNull<Node<T>>>,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+pub struct CursorMut<'a, T: 'a> {
+    prev: Option<NonNull<Node<T>>>,
+    list: &'a mut CircularLinkedList<T>,
+}
+
 impl<T> CircularLinkedList<T> {
     pub fn new() -> Self {
         Self {
             tail: None,
+            len: 0,
             _marker: PhantomData
         }
     }
@@ -45,23 +61,31 @@ impl<T> CircularLinkedList<T> {
         self.tail.is_none()
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     pub fn clear(&mut self) {
         *self = Self::new()
         // drop(self) is called
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    fn head(&self) -> Option<NonNull<Node<T>>> {
+        self.tail.and_then(|tail_ptr| unsafe { tail_ptr.as_ref() }.next)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter { 
-            cur: self.tail.as_ref().map(|tail_ptr| unsafe { tail_ptr.as_ref() }.next.unwrap()), 
-            tail: self.tail,
+            cur: self.head(), 
+            remaining: self.len,
             _marker: PhantomData
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut { 
-            cur: self.tail.as_mut().map(|tail_ptr| unsafe { tail_ptr.as_mut() }.next.unwrap()), 
-            tail: self.tail,
+            cur: self.head(), 
+            remaining: self.len,
             _marker: PhantomData
         }
     }
@@ -75,11 +99,11 @@ impl<T> CircularLinkedList<T> {
 
 impl<T> Drop for CircularLinkedList<T> {
     fn drop(&mut self) {
-        let mut cur = self.tail;
+        // break the ring after the tail, then free the nodes from the head onwards
+        let mut cur = self.tail.take().and_then(|mut tail_ptr| unsafe { tail_ptr.as_mut() }.next.take());
         while let Some(node_ptr) = cur {
             let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
             cur = node.next;
-            if cur == self.tail { break }
             // drop(node) is called
         }
     }
@@ -96,48 +120,44 @@ impl<T> CircularLinkedList<T> {
     }
 
     pub fn front(&self) -> Option<&T> {
-        self.tail.as_ref().map(|node_ptr| &unsafe { &*node_ptr.as_ref().next.unwrap().as_ptr() }.elem)
+        self.head().map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.elem)
     }
 
     pub fn front_mut(&mut self) -> Option<&mut T> {
-        self.tail.as_mut().map(|node_ptr| &mut unsafe { &mut *node_ptr.as_mut().next.unwrap().as_ptr() }.elem)
+        self.head().map(|node_ptr| &mut unsafe { &mut *node_ptr.as_ptr() }.elem)
     }
     
     pub fn push_front(&mut self, elem: T) {
         if let Some(mut tail_ptr) = self.tail {
             let head_ptr = unsafe { tail_ptr.as_ref() }.next;
             let node = Box::new(Node { elem, next: head_ptr });
-            let new_head_ptr = NonNull::new(Box::into_raw(node));
-            assert!(new_head_ptr.is_some());
-            unsafe { tail_ptr.as_mut() }.next = new_head_ptr;
+            unsafe { tail_ptr.as_mut() }.next = NonNull::new(Box::into_raw(node));
         } else {
             let node = Box::new(Node { elem, next: None });
             let new_head_ptr = NonNull::new(Box::into_raw(node));
-            assert!(new_head_ptr.is_some());
             unsafe { new_head_ptr.unwrap().as_mut() }.next = new_head_ptr;
             self.tail = new_head_ptr;
         } 
+        self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
         self.tail.map(|mut tail_ptr| {
-            let head_ptr = unsafe { tail_ptr.as_ref() }.next; 
-            assert!(head_ptr.is_some());
-            if head_ptr == self.tail {
+            let head_ptr = unsafe { tail_ptr.as_ref() }.next.unwrap(); 
+            if head_ptr == tail_ptr {
                 self.tail = None;
             } else {
-                let new_head_ptr = unsafe { head_ptr.unwrap().as_ref() }.next; 
-                unsafe { tail_ptr.as_mut() }.next = new_head_ptr;
+                unsafe { tail_ptr.as_mut() }.next = unsafe { head_ptr.as_ref() }.next;
             }
-            let head = unsafe { Box::from_raw(head_ptr.unwrap().as_ptr()) };
+            self.len -= 1;
+            let head = unsafe { Box::from_raw(head_ptr.as_ptr()) };
             head.elem
         })
     }
 
     pub fn push_back(&mut self, elem: T) {
         self.push_front(elem);
-        assert!(self.tail.is_some());
-        self.tail = unsafe { self.tail.unwrap().as_ref() }.next;
+        self.tail = self.head();
     }
 
     pub fn append(&mut self, other: &mut Self) {
@@ -146,6 +166,8 @@ impl<T> CircularLinkedList<T> {
             (Some(_tail_ptr), None) => {}, // append an empty list, nothing to do
             (Some(mut tail_ptr), Some(mut other_tail_ptr)) => {
                 mem::swap(&mut unsafe { tail_ptr.as_mut() }.next, &mut unsafe { other_tail_ptr.as_mut() }.next);
+                self.tail = other.tail.take();
+                self.len += mem::replace(&mut other.len, 0);
             }
         }
     }
@@ -153,63 +175,109 @@ impl<T> CircularLinkedList<T> {
 
 // O(n) operations
 impl<T> CircularLinkedList<T> {
-    pub fn len(&self) -> usize {
-        let mut cur = self.tail;
-        let mut ans = 0;
-        while let Some(node_ptr) = cur {
-            ans += 1;
-            cur = unsafe { node_ptr.as_ref() }.next;
-            if cur == self.tail { break }
-        } 
-        ans
-    } 
+    // Node at position `at` counting from the head, where `at` is less than `self.len`
+    fn node_at(&self, at: usize) -> NonNull<Node<T>> {
+        let mut cur = self.head().unwrap();
+        for _ in 0..at {
+            cur = unsafe { cur.as_ref() }.next.unwrap();
+        }
+        cur
+    }
 
     pub fn pop_back(&mut self) -> Option<T> {
-        let mut new_tail = None;
-        let mut cur = self.tail;
-        while let Some(node_ptr) = cur {
-            new_tail = cur;
-            cur = unsafe { node_ptr.as_ref() }.next;
-            if cur == self.tail { break }
-        } 
-        drop(cur);
-        new_tail.map(|mut new_tail_ptr| {
-            assert!(self.tail.is_some());
-            unsafe { new_tail_ptr.as_mut() }.next = unsafe { self.tail.unwrap().as_ref() }.next;
-            let ans = unsafe { Box::from_raw(self.tail.unwrap().as_ptr()) };
-            self.tail = new_tail;
-            ans.elem
-        })
+        match self.len {
+            0 => None,
+            1 => self.pop_front(),
+            len => {
+                let mut new_tail_ptr = self.node_at(len - 2);
+                let old_tail_ptr = self.tail.unwrap();
+                unsafe { new_tail_ptr.as_mut() }.next = unsafe { old_tail_ptr.as_ref() }.next;
+                self.tail = Some(new_tail_ptr);
+                self.len -= 1;
+                let node = unsafe { Box::from_raw(old_tail_ptr.as_ptr()) };
+                Some(node.elem)
+            }
+        }
     }
 
     pub fn split_off(&mut self, at: usize) -> CircularLinkedList<T> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
         if at == 0 {
-            return mem::replace(self, Self::new())
+            return mem::take(self)
         }
-        let mut cur = self.tail;
-        let mut next_id = 0;
-        while let Some(node_ptr) = cur {
-            if next_id == at {
-                break;
-            }
-            cur = unsafe { node_ptr.as_ref() }.next;
-            next_id += 1;
+        if at == self.len {
+            return Self::new()
         }
-        assert!(at <= next_id, "Cannot split off a nonexistent index");
-        if let Some(mut node_ptr) = cur {
-            let second_part = Self {
-                tail: self.tail,
-                _marker: PhantomData
-            };
-            mem::swap(&mut unsafe { node_ptr.as_mut() }.next, 
-                &mut unsafe { self.tail.unwrap().as_mut() }.next);
-            second_part
-        } else {
-            Self::new()
+        let mut new_tail_ptr = self.node_at(at - 1);
+        let mut old_tail_ptr = self.tail.unwrap();
+        // the two rings are closed by exchanging the successors of the two tails
+        mem::swap(&mut unsafe { new_tail_ptr.as_mut() }.next, 
+            &mut unsafe { old_tail_ptr.as_mut() }.next);
+        let second_part = Self {
+            tail: Some(old_tail_ptr),
+            len: self.len - at,
+            _marker: PhantomData
+        };
+        self.tail = Some(new_tail_ptr);
+        self.len = at;
+        second_part
+    }
+}
+
+// Ring operations
+impl<T> CircularLinkedList<T> {
+    // Moves the first `n` elements to the back, walking `n % len` nodes
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.len == 0 || n % self.len == 0 {
+            return;
+        }
+        self.tail = Some(self.node_at(n % self.len - 1));
+    }
+
+    // Moves the last `n` elements to the front, walking `len - n % len` nodes. A singly linked
+    // ring can only walk forward, so unlike `rotate_left` this is not O(n mod len): rotating
+    // right by 1 walks all but one node of the ring.
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        self.rotate_left(self.len - n % self.len);
+    }
+
+    // Iterates over every element once, starting from position `start % len`
+    pub fn iter_from(&self, start: usize) -> Iter<'_, T> {
+        Iter {
+            cur: if self.len == 0 { None } else { Some(self.node_at(start % self.len)) },
+            remaining: self.len,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor { prev: self.tail, _marker: PhantomData }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { prev: self.tail, list: self }
+    }
+
+    // Josephus elimination: counting from the front, removes every `k`-th remaining element
+    // until the list is empty, and returns the elements in the order they were removed
+    pub fn remove_every_kth(&mut self, k: usize) -> Vec<T> {
+        assert!(k > 0, "Cannot remove every 0th element");
+        let mut ans = Vec::with_capacity(self.len);
+        let mut cursor = self.cursor_front_mut();
+        while !cursor.list.is_empty() {
+            for _ in 0..(k - 1) % cursor.list.len {
+                cursor.move_next();
+            }
+            ans.extend(cursor.remove_current());
         }
+        ans
     }
 }
-impl<'a, T> IntoIterator for CircularLinkedList<T> {
+
+impl<T> IntoIterator for CircularLinkedList<T> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
@@ -254,10 +322,6 @@ impl<T: PartialEq> PartialEq for CircularLinkedList<T> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().eq(other)
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.iter().ne(other)
-    }
 }
 
 impl<T: Eq> Eq for CircularLinkedList<T> {}
@@ -316,17 +380,24 @@ impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
-        if self.cur == self.tail {
+        if self.remaining == 0 {
             return None;
         }
+        self.remaining -= 1;
         self.cur.map(|cur_ptr| {
             let node = unsafe { &*cur_ptr.as_ptr() };
             self.cur = node.next;
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
 impl<'a, T> FusedIterator for Iter<'a, T> {}
 
 impl<'a, T: 'a + fmt::Debug> fmt::Debug for Iter<'a, T> {
@@ -343,17 +414,24 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<&'a mut T> {
-        if self.cur == self.tail {
+        if self.remaining == 0 {
             return None;
         }
+        self.remaining -= 1;
         self.cur.map(|cur_ptr| {
             let node = unsafe { &mut *cur_ptr.as_ptr() };
             self.cur = node.next;
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
 impl<'a, T> FusedIterator for IterMut<'a, T> {}
 
 impl<'a, T: 'a + fmt::Debug> fmt::Debug for IterMut<'a, T> {
@@ -372,6 +450,263 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<T> {
         self.list.pop_front()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 impl<T> FusedIterator for IntoIter<T> {}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn move_next(&mut self) {
+        self.prev = self.prev.and_then(|prev_ptr| unsafe { prev_ptr.as_ref() }.next);
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        self.prev.map(|prev_ptr| &unsafe { &*prev_ptr.as_ref().next.unwrap().as_ptr() }.elem)
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.prev.map(|prev_ptr| {
+            let cur_ptr = unsafe { prev_ptr.as_ref() }.next.unwrap();
+            &unsafe { &*cur_ptr.as_ref().next.unwrap().as_ptr() }.elem
+        })
+    }
+}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor { prev: self.prev, _marker: PhantomData }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for Cursor<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cursor").field(&self.current()).finish()
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn move_next(&mut self) {
+        self.prev = self.prev.and_then(|prev_ptr| unsafe { prev_ptr.as_ref() }.next);
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.prev.map(|prev_ptr| &mut unsafe { &mut *prev_ptr.as_ref().next.unwrap().as_ptr() }.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.prev.map(|prev_ptr| {
+            let cur_ptr = unsafe { prev_ptr.as_ref() }.next.unwrap();
+            &mut unsafe { &mut *cur_ptr.as_ref().next.unwrap().as_ptr() }.elem
+        })
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor { prev: self.prev, _marker: PhantomData }
+    }
+
+    // Inserts after the current element; into an empty list, the new element becomes current
+    pub fn insert_after(&mut self, elem: T) {
+        let mut cur_ptr = match self.prev {
+            Some(prev_ptr) => unsafe { prev_ptr.as_ref() }.next.unwrap(),
+            None => {
+                self.list.push_back(elem);
+                self.prev = self.list.tail;
+                return;
+            }
+        };
+        let node = Box::new(Node { elem, next: unsafe { cur_ptr.as_ref() }.next });
+        let node_ptr = NonNull::new(Box::into_raw(node));
+        unsafe { cur_ptr.as_mut() }.next = node_ptr;
+        if self.list.tail == Some(cur_ptr) {
+            self.list.tail = node_ptr;
+        }
+        if self.prev == Some(cur_ptr) {
+            self.prev = node_ptr; // the current element was alone in the ring
+        }
+        self.list.len += 1;
+    }
+
+    // Unlinks the current element; the cursor moves on to the one after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let mut prev_ptr = self.prev?;
+        let cur_ptr = unsafe { prev_ptr.as_ref() }.next.unwrap();
+        if cur_ptr == prev_ptr {
+            self.list.tail = None;
+            self.prev = None;
+        } else {
+            unsafe { prev_ptr.as_mut() }.next = unsafe { cur_ptr.as_ref() }.next;
+            if self.list.tail == Some(cur_ptr) {
+                self.list.tail = Some(prev_ptr);
+            }
+        }
+        self.list.len -= 1;
+        let node = unsafe { Box::from_raw(cur_ptr.as_ptr()) };
+        Some(node.elem)
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CursorMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.list).field(&self.as_cursor().current()).finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    impl<T> CircularLinkedList<T> {
+        fn check_invariants(&self) {
+            let tail_ptr = match self.tail {
+                Some(tail_ptr) => tail_ptr,
+                None => return assert_eq!(self.len, 0),
+            };
+            let mut cur = unsafe { tail_ptr.as_ref() }.next.unwrap();
+            let mut len = 1;
+            while cur != tail_ptr {
+                len += 1;
+                cur = unsafe { cur.as_ref() }.next.unwrap();
+            }
+            assert_eq!(len, self.len);
+        }
+    }
+
+    #[test]
+    fn iter_yields_tail() {
+        let mut list: CircularLinkedList<u32> = (1..=4).collect();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(list.iter().len(), 4);
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+        let single: CircularLinkedList<u32> = Some(7).into_iter().collect();
+        assert_eq!(single.iter().collect::<Vec<_>>(), vec![&7]);
+        let into_iter = list.into_iter();
+        assert_eq!(into_iter.clone().collect::<Vec<_>>(), into_iter.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        for len in 0..6 {
+            for at in 0..=len {
+                let mut list: CircularLinkedList<usize> = (0..len).collect();
+                let mut second = list.split_off(at);
+                list.check_invariants();
+                second.check_invariants();
+                assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (0..at).collect::<Vec<_>>());
+                assert_eq!(second.iter().cloned().collect::<Vec<_>>(), (at..len).collect::<Vec<_>>());
+                assert_eq!(list.back(), at.checked_sub(1).as_ref());
+                list.append(&mut second);
+                list.check_invariants();
+                second.check_invariants();
+                assert!(second.is_empty());
+                assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        let mut list: CircularLinkedList<u32> = (0..5).collect();
+        list.rotate_left(2);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![2, 3, 4, 0, 1]);
+        list.rotate_right(12);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        list.rotate_left(5);
+        assert_eq!((list.front(), list.back()), (Some(&0), Some(&4)));
+        list.rotate_right(1);
+        assert_eq!((list.front(), list.back()), (Some(&4), Some(&3)));
+        list.check_invariants();
+        let mut empty: CircularLinkedList<u32> = CircularLinkedList::new();
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn iter_from() {
+        let list: CircularLinkedList<u32> = (0..4).collect();
+        assert_eq!(list.iter_from(2).cloned().collect::<Vec<_>>(), vec![2, 3, 0, 1]);
+        assert_eq!(list.iter_from(9).cloned().collect::<Vec<_>>(), vec![1, 2, 3, 0]);
+        assert_eq!(CircularLinkedList::<u32>::new().iter_from(3).next(), None);
+    }
+
+    #[test]
+    fn wrapping_cursor() {
+        let mut list: CircularLinkedList<u32> = (0..3).collect();
+        let mut cursor = list.cursor_front();
+        let mut seen = Vec::new();
+        for _ in 0..7 {
+            seen.push(*cursor.current().unwrap());
+            cursor.move_next();
+        }
+        assert_eq!(seen, vec![0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(cursor.peek_next(), Some(&2));
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_after(3);
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        *cursor.current().unwrap() = 10;
+        list.check_invariants();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![10, 1, 3]);
+        assert_eq!(list.back(), Some(&3));
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(5);
+        cursor.insert_after(6);
+        assert_eq!(cursor.current(), Some(&mut 5));
+        list.check_invariants();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![5, 6]);
+    }
+
+    #[test]
+    fn josephus() {
+        let mut list: CircularLinkedList<u32> = (1..=7).collect();
+        assert_eq!(list.remove_every_kth(3), vec![3, 6, 2, 7, 5, 1, 4]);
+        assert!(list.is_empty());
+        list.check_invariants();
+        let mut list: CircularLinkedList<u32> = (1..=41).collect();
+        assert_eq!(list.remove_every_kth(3).last(), Some(&31));
+        let mut list: CircularLinkedList<u32> = (1..=5).collect();
+        assert_eq!(list.remove_every_kth(1), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn drop_every_node() {
+        let counter = Rc::new(());
+        let mut list: CircularLinkedList<Rc<()>> = (0..10).map(|_| counter.clone()).collect();
+        list.pop_back();
+        let second = list.split_off(4);
+        assert_eq!(Rc::strong_count(&counter), 10);
+        drop(second);
+        assert_eq!(Rc::strong_count(&counter), 5);
+        drop(list);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,733 @@
+use core::cmp::Ordering;
+use core::hash::{Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use core::ptr::NonNull;
+use core::marker::PhantomData;
+use core::mem;
+
+pub struct DoublyLinkedList<T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<Box<Node<T>>>
+}
+
+struct Node<T> {
+    elem: T,
+    prev: Option<NonNull<Node<T>>>,
+    next: Option<NonNull<Node<T>>>
+}
+
+pub struct Iter<'a, T: 'a> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+pub struct IterMut<'a, T: 'a> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+#[derive(Clone)]
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>
+}
+
+// A cursor points at an element or at the "ghost" non-element between the tail and the head.
+// The ghost's index is `len`.
+pub struct CursorMut<'a, T: 'a> {
+    index: usize,
+    current: Option<NonNull<Node<T>>>,
+    list: &'a mut DoublyLinkedList<T>,
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        self.iter().any(|elem| elem == value)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new()
+        // drop(self) is called
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: 0, current: self.head, list: self }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: self.len.saturating_sub(1), current: self.tail, list: self }
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head;
+        while let Some(node_ptr) = cur {
+            let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+            cur = node.next;
+            // drop(node) is called
+        }
+    }
+}
+
+// Linking primitives, all O(1)
+impl<T> DoublyLinkedList<T> {
+    // Links a new node between `prev` and `next`, which must be adjacent (`None` is the ghost)
+    fn insert_between(&mut self, prev: Option<NonNull<Node<T>>>, next: Option<NonNull<Node<T>>>, elem: T) -> NonNull<Node<T>> {
+        let node = Box::new(Node { elem, prev, next });
+        let node_ptr = NonNull::new(Box::into_raw(node));
+        match prev {
+            Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = node_ptr,
+            None => self.head = node_ptr,
+        }
+        match next {
+            Some(mut next_ptr) => unsafe { next_ptr.as_mut() }.prev = node_ptr,
+            None => self.tail = node_ptr,
+        }
+        self.len += 1;
+        node_ptr.unwrap()
+    }
+
+    fn unlink(&mut self, node_ptr: NonNull<Node<T>>) -> T {
+        let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+        match node.prev {
+            Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(mut next_ptr) => unsafe { next_ptr.as_mut() }.prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.elem
+    }
+
+    // Moves all of `other` between the adjacent `prev` and `next`
+    fn splice_between(&mut self, prev: Option<NonNull<Node<T>>>, next: Option<NonNull<Node<T>>>, mut other: Self) {
+        let (mut other_head, mut other_tail) = match (other.head.take(), other.tail.take()) {
+            (Some(head_ptr), Some(tail_ptr)) => (head_ptr, tail_ptr),
+            _ => return,
+        };
+        unsafe { other_head.as_mut() }.prev = prev;
+        unsafe { other_tail.as_mut() }.next = next;
+        match prev {
+            Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = Some(other_head),
+            None => self.head = Some(other_head),
+        }
+        match next {
+            Some(mut next_ptr) => unsafe { next_ptr.as_mut() }.prev = Some(other_tail),
+            None => self.tail = Some(other_tail),
+        }
+        self.len += mem::replace(&mut other.len, 0);
+    }
+
+    // Splits off the nodes after `node`, which is at position `at - 1`; `None` splits off everything
+    fn split_after_node(&mut self, node: Option<NonNull<Node<T>>>, at: usize) -> Self {
+        let second_head = match node {
+            Some(mut node_ptr) => unsafe { node_ptr.as_mut() }.next.take(),
+            None => self.head.take(),
+        };
+        let mut second_head_ptr = match second_head {
+            Some(head_ptr) => head_ptr,
+            None => return Self::new(),
+        };
+        unsafe { second_head_ptr.as_mut() }.prev = None;
+        let second_part = Self {
+            head: second_head,
+            tail: self.tail,
+            len: self.len - at,
+            _marker: PhantomData
+        };
+        self.tail = node;
+        self.len = at;
+        second_part
+    }
+}
+
+// O(1) operations
+impl<T> DoublyLinkedList<T> {
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.insert_between(None, self.head, elem);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head_ptr| self.unlink(head_ptr))
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.insert_between(self.tail, None, elem);
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|tail_ptr| self.unlink(tail_ptr))
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.splice_between(self.tail, None, mem::take(other));
+    }
+}
+
+// O(n) operations
+impl<T> DoublyLinkedList<T> {
+    // Walks from whichever end is closer to `at`, which must be less than `self.len`
+    fn node_at(&self, at: usize) -> NonNull<Node<T>> {
+        if at < self.len / 2 {
+            let mut cur = self.head.unwrap();
+            for _ in 0..at {
+                cur = unsafe { cur.as_ref() }.next.unwrap();
+            }
+            cur
+        } else {
+            let mut cur = self.tail.unwrap();
+            for _ in at + 1..self.len {
+                cur = unsafe { cur.as_ref() }.prev.unwrap();
+            }
+            cur
+        }
+    }
+
+    pub fn split_off(&mut self, at: usize) -> DoublyLinkedList<T> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
+        if at == 0 {
+            return mem::take(self)
+        }
+        let node_ptr = self.node_at(at - 1);
+        self.split_after_node(Some(node_ptr), at)
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    fn next_node(&self) -> Option<NonNull<Node<T>>> {
+        match self.current {
+            None => self.list.head,
+            Some(cur_ptr) => unsafe { cur_ptr.as_ref() }.next,
+        }
+    }
+
+    fn prev_node(&self) -> Option<NonNull<Node<T>>> {
+        match self.current {
+            None => self.list.tail,
+            Some(cur_ptr) => unsafe { cur_ptr.as_ref() }.prev,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = self.next_node();
+        self.index = match self.current {
+            Some(_) if self.index == self.list.len => 0,
+            Some(_) => self.index + 1,
+            None => self.list.len,
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = self.prev_node();
+        self.index = match self.current {
+            Some(_) if self.index == 0 => self.list.len - 1,
+            Some(_) => self.index - 1,
+            None => self.list.len,
+        };
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|cur_ptr| &mut unsafe { &mut *cur_ptr.as_ptr() }.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_node().map(|next_ptr| &mut unsafe { &mut *next_ptr.as_ptr() }.elem)
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.prev_node().map(|prev_ptr| &mut unsafe { &mut *prev_ptr.as_ptr() }.elem)
+    }
+
+    // Inserts after the current element, or at the front when at the ghost
+    pub fn insert_after(&mut self, elem: T) {
+        let next = self.next_node();
+        self.list.insert_between(self.current, next, elem);
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    // Inserts before the current element, or at the back when at the ghost
+    pub fn insert_before(&mut self, elem: T) {
+        let prev = self.prev_node();
+        self.list.insert_between(prev, self.current, elem);
+        self.index += 1;
+    }
+
+    // Unlinks the current element; the cursor moves on to the one after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur_ptr = self.current?;
+        self.current = unsafe { cur_ptr.as_ref() }.next;
+        let ans = self.list.unlink(cur_ptr);
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+        Some(ans)
+    }
+
+    // Splits off everything after the current element, or the whole list when at the ghost
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let at = self.current.map_or(0, |_| self.index + 1);
+        let ans = self.list.split_after_node(self.current, at);
+        if self.current.is_none() {
+            self.index = 0;
+        }
+        ans
+    }
+
+    // Splits off everything before the current element, or the whole list when at the ghost
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let at = self.current.map_or(self.list.len, |_| self.index);
+        let second_part = self.list.split_after_node(self.prev_node(), at);
+        let first_part = mem::replace(self.list, second_part);
+        self.index = self.current.map_or(self.list.len, |_| 0);
+        first_part
+    }
+
+    // Moves all of `other` after the current element, or to the front when at the ghost
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        let other_len = other.len;
+        let next = self.next_node();
+        self.list.splice_between(self.current, next, other);
+        if self.current.is_none() {
+            self.index += other_len;
+        }
+    }
+
+    // Moves all of `other` before the current element, or to the back when at the ghost
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        self.index += other.len;
+        let prev = self.prev_node();
+        self.list.splice_between(prev, self.current, other);
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CursorMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for DoublyLinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for DoublyLinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|head_ptr| {
+            let node = unsafe { &*head_ptr.as_ptr() };
+            self.len -= 1;
+            self.head = node.next;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|tail_ptr| {
+            let node = unsafe { &*tail_ptr.as_ptr() };
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.len).finish()
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|head_ptr| {
+            let node = unsafe { &mut *head_ptr.as_ptr() };
+            self.len -= 1;
+            self.head = node.next;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|tail_ptr| {
+            let node = unsafe { &mut *tail_ptr.as_ptr() };
+            self.len -= 1;
+            self.tail = node.prev;
+            &mut node.elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for IterMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IterMut").field(&self.len).finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl<T> DoublyLinkedList<T> {
+        fn check_invariants(&self) {
+            let mut cur = self.head;
+            let mut prev = None;
+            let mut len = 0;
+            while let Some(node_ptr) = cur {
+                assert_eq!(unsafe { node_ptr.as_ref() }.prev, prev);
+                len += 1;
+                prev = cur;
+                cur = unsafe { node_ptr.as_ref() }.next;
+            }
+            assert_eq!(prev, self.tail);
+            assert_eq!(len, self.len);
+        }
+    }
+
+    fn to_vec(list: &DoublyLinkedList<u32>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn double_ended_iter() {
+        let mut list: DoublyLinkedList<u32> = (0..6).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!((iter.next(), iter.next_back()), (Some(&0), Some(&5)));
+        assert_eq!(iter.clone().rev().collect::<Vec<_>>(), vec![&4, &3, &2, &1]);
+        assert_eq!(iter.len(), 4);
+        for elem in list.iter_mut().rev().take(2) {
+            *elem += 10;
+        }
+        assert_eq!(to_vec(&list), vec![0, 1, 2, 3, 14, 15]);
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(15));
+        assert_eq!(into_iter.len(), 5);
+    }
+
+    #[test]
+    fn split_off_both_halves() {
+        for len in 0..8 {
+            for at in 0..=len {
+                let mut list: DoublyLinkedList<u32> = (0..len).collect();
+                let mut second = list.split_off(at as usize);
+                list.check_invariants();
+                second.check_invariants();
+                assert_eq!(to_vec(&list), (0..at).collect::<Vec<_>>());
+                assert_eq!(to_vec(&second), (at..len).collect::<Vec<_>>());
+                list.append(&mut second);
+                list.check_invariants();
+                second.check_invariants();
+                assert_eq!(to_vec(&list), (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_mut_moves() {
+        let mut list: DoublyLinkedList<u32> = (0..3).collect();
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 2)));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(cursor.peek_prev(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 2)));
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(1));
+    }
+
+    #[test]
+    fn cursor_mut_edits() {
+        let mut list: DoublyLinkedList<u32> = (0..4).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(10);
+        cursor.insert_after(11);
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 1)));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 11)));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(20);
+        cursor.insert_before(21);
+        assert_eq!(cursor.index(), None);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![20, 0, 10, 11, 2, 21]);
+    }
+
+    #[test]
+    fn cursor_mut_split_splice() {
+        let mut list: DoublyLinkedList<u32> = (0..6).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let before = cursor.split_before();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&mut 2)));
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.splice_before(after);
+        cursor.splice_after(before);
+        assert_eq!((cursor.index(), cursor.current()), (Some(3), Some(&mut 2)));
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.splice_after((7..9).collect());
+        cursor.splice_before((9..10).collect());
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(8), Some(&mut 9)));
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![7, 8, 3, 4, 5, 2, 0, 1, 9]);
+
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        assert_eq!(cursor.split_before().len(), 9);
+        assert!(list.is_empty());
+        list.check_invariants();
+    }
+}
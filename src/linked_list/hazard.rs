@@ -0,0 +1,158 @@
+// Hazard pointers for the lock-free lists.
+//
+// A thread that is about to dereference a shared node publishes the node's address in a
+// hazard record first. Removed nodes are retired rather than freed, and a retired node is
+// only freed once no hazard record holds its address.
+use core::ptr;
+use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+struct HazardRecord {
+    ptr: AtomicPtr<u8>,
+    active: AtomicBool,
+    // written once before the record is published, never changed afterwards
+    next: *mut HazardRecord,
+}
+
+// Records are never freed, released ones are reused by later `HazardPointer::new` calls
+static RECORDS: AtomicPtr<HazardRecord> = AtomicPtr::new(ptr::null_mut());
+static RECORD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct HazardPointer {
+    record: &'static HazardRecord,
+}
+
+impl HazardPointer {
+    pub fn new() -> Self {
+        let mut cur = RECORDS.load(Ordering::Acquire);
+        while let Some(record) = unsafe { cur.as_ref() } {
+            if !record.active.load(Ordering::Relaxed) &&
+                record.active.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                return Self { record };
+            }
+            cur = record.next;
+        }
+        let record = Box::into_raw(Box::new(HazardRecord {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = RECORDS.load(Ordering::Acquire);
+        loop {
+            unsafe { (*record).next = head };
+            match RECORDS.compare_exchange_weak(head, record, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => break,
+                Err(actual) => head = actual,
+            }
+        }
+        RECORD_COUNT.fetch_add(1, Ordering::Relaxed);
+        Self { record: unsafe { &*record } }
+    }
+
+    // Loads `src` and protects the loaded pointer; it stays valid until the protection is
+    // cleared or replaced, as long as the owner of `src` retires nodes through `retire`
+    pub fn protect<T>(&self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.record.ptr.store(ptr as *mut u8, Ordering::SeqCst);
+            let reloaded = src.load(Ordering::SeqCst);
+            if reloaded == ptr {
+                return ptr;
+            }
+            ptr = reloaded;
+        }
+    }
+
+    pub fn clear(&self) {
+        self.record.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Default for HazardPointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        self.clear();
+        self.record.active.store(false, Ordering::Release);
+    }
+}
+
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+}
+
+unsafe impl Send for Retired {}
+
+// Retired nodes left behind by threads that exited before they could be freed
+static ORPHANS: Mutex<Vec<Retired>> = Mutex::new(Vec::new());
+
+struct RetiredList(Vec<Retired>);
+
+impl Drop for RetiredList {
+    fn drop(&mut self) {
+        scan(&mut self.0);
+        if !self.0.is_empty() {
+            if let Ok(mut orphans) = ORPHANS.lock() {
+                orphans.append(&mut self.0);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static RETIRED: RefCell<RetiredList> = const { RefCell::new(RetiredList(Vec::new())) };
+}
+
+unsafe fn free_box<T>(ptr: *mut u8) {
+    drop(unsafe { Box::from_raw(ptr as *mut T) });
+}
+
+// Frees every retired node that no hazard pointer protects
+fn scan(retired: &mut Vec<Retired>) {
+    fence(Ordering::SeqCst);
+    let mut hazards = Vec::new();
+    let mut cur = RECORDS.load(Ordering::Acquire);
+    while let Some(record) = unsafe { cur.as_ref() } {
+        let ptr = record.ptr.load(Ordering::SeqCst);
+        if !ptr.is_null() {
+            hazards.push(ptr);
+        }
+        cur = record.next;
+    }
+    hazards.sort_unstable();
+    retired.retain(|node| {
+        if hazards.binary_search(&node.ptr).is_ok() {
+            return true;
+        }
+        unsafe { (node.free)(node.ptr) };
+        false
+    });
+}
+
+// Frees the `Box`-allocated `ptr` once no hazard pointer protects it.
+// Safety: `ptr` must be unreachable from the shared structure, and retired only once.
+pub unsafe fn retire<T>(ptr: *mut T) {
+    let mut node = Some(Retired { ptr: ptr as *mut u8, free: free_box::<T> });
+    let _ = RETIRED.try_with(|list| {
+        let list = &mut list.borrow_mut().0;
+        list.push(node.take().unwrap());
+        if list.len() >= 2 * RECORD_COUNT.load(Ordering::Relaxed) + 64 {
+            if let Ok(mut orphans) = ORPHANS.try_lock() {
+                list.append(&mut orphans);
+            }
+            scan(list);
+        }
+    });
+    // the thread is exiting and its retired list is gone
+    if let Some(node) = node {
+        if let Ok(mut orphans) = ORPHANS.lock() {
+            orphans.push(node);
+        }
+    }
+}
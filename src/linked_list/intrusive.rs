@@ -0,0 +1,534 @@
+use core::cell::Cell;
+use core::iter::FusedIterator;
+use core::fmt;
+use core::marker::{PhantomData, PhantomPinned};
+use core::pin::Pin;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// Lists that link caller-owned values through a `Link` field embedded in them, without
+// allocating or taking ownership. A list borrows its values for `'a`, so they can neither
+// move nor drop while linked, and it unlinks whatever is left when it is dropped.
+// A value with several `Link` fields can sit on several lists at once, one adapter per field.
+pub struct Link {
+    // pointers to the neighbouring values, not to their links
+    next: Cell<Option<NonNull<u8>>>,
+    prev: Cell<Option<NonNull<u8>>>,
+    // id of the list holding this link, 0 while unlinked
+    owner: Cell<usize>,
+    _pinned: PhantomPinned,
+}
+
+impl Link {
+    pub const fn new() -> Self {
+        Self {
+            next: Cell::new(None),
+            prev: Cell::new(None),
+            owner: Cell::new(0),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+
+    fn reset(&self) {
+        self.next.set(None);
+        self.prev.set(None);
+        self.owner.set(0);
+    }
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Link").field("is_linked", &self.is_linked()).finish()
+    }
+}
+
+/// Picks the `Link` field that a list uses.
+///
+/// # Safety
+///
+/// `link` must return the same field of `value` on every call, and no two adapters
+/// used at the same time may return the same field.
+pub unsafe trait Adapter {
+    type Value;
+
+    fn link(value: &Self::Value) -> &Link;
+}
+
+// `intrusive_adapter!(pub TimerAdapter = Timer { link })` declares an adapter for the
+// `link: Link` field of `Timer`
+#[macro_export]
+macro_rules! intrusive_adapter {
+    ($vis: vis $name: ident = $value: ty { $field: ident }) => {
+        $vis struct $name;
+
+        unsafe impl $crate::linked_list::intrusive::Adapter for $name {
+            type Value = $value;
+
+            fn link(value: &$value) -> &$crate::linked_list::intrusive::Link {
+                &value.$field
+            }
+        }
+    };
+}
+
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+fn next_list_id() -> usize {
+    NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn link_of<'a, A: Adapter>(value_ptr: NonNull<A::Value>) -> &'a Link
+where A::Value: 'a
+{
+    A::link(unsafe { &*value_ptr.as_ptr() })
+}
+
+fn pinned<'a, A: Adapter>(value_ptr: NonNull<A::Value>) -> Pin<&'a A::Value>
+where A::Value: 'a
+{
+    unsafe { Pin::new_unchecked(&*value_ptr.as_ptr()) }
+}
+
+pub struct SinglyList<'a, A: Adapter> {
+    head: Option<NonNull<A::Value>>,
+    tail: Option<NonNull<A::Value>>,
+    len: usize,
+    id: usize,
+    _marker: PhantomData<(&'a A::Value, A)>
+}
+
+pub struct DoublyList<'a, A: Adapter> {
+    head: Option<NonNull<A::Value>>,
+    tail: Option<NonNull<A::Value>>,
+    len: usize,
+    id: usize,
+    _marker: PhantomData<(&'a A::Value, A)>
+}
+
+pub struct Iter<'a, A: Adapter> {
+    cur: Option<NonNull<A::Value>>,
+    remaining: usize,
+    _marker: PhantomData<&'a A::Value>,
+}
+
+impl<'a, A: Adapter> SinglyList<'a, A> {
+    pub fn new() -> Self {
+        Self { head: None, tail: None, len: 0, id: next_list_id(), _marker: PhantomData }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn front(&self) -> Option<Pin<&'a A::Value>> {
+        self.head.map(pinned::<A>)
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a A::Value>> {
+        self.tail.map(pinned::<A>)
+    }
+
+    // O(1), whether `value` is linked into this very list
+    pub fn contains(&self, value: &A::Value) -> bool {
+        A::link(value).owner.get() == self.id
+    }
+
+    fn claim(&self, value: Pin<&'a A::Value>) -> NonNull<A::Value> {
+        let link = A::link(value.get_ref());
+        assert!(!link.is_linked(), "Cannot link a value that is already on a list");
+        link.owner.set(self.id);
+        NonNull::from(value.get_ref())
+    }
+
+    pub fn push_front(&mut self, value: Pin<&'a A::Value>) {
+        let value_ptr = self.claim(value);
+        link_of::<A>(value_ptr).next.set(self.head.map(NonNull::cast));
+        self.head = Some(value_ptr);
+        if self.tail.is_none() {
+            self.tail = self.head;
+        }
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: Pin<&'a A::Value>) {
+        let value_ptr = self.claim(value);
+        match self.tail {
+            Some(tail_ptr) => link_of::<A>(tail_ptr).next.set(Some(value_ptr.cast())),
+            None => self.head = Some(value_ptr),
+        }
+        self.tail = Some(value_ptr);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<&'a A::Value>> {
+        self.head.map(|head_ptr| {
+            let link = link_of::<A>(head_ptr);
+            self.head = link.next.get().map(NonNull::cast);
+            if self.head.is_none() {
+                self.tail = None;
+            }
+            self.len -= 1;
+            link.reset();
+            pinned::<A>(head_ptr)
+        })
+    }
+
+    // O(n), unlinks `value` if it is on this list
+    pub fn remove(&mut self, value: &A::Value) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let target = NonNull::from(value);
+        let mut prev: Option<NonNull<A::Value>> = None;
+        let mut cur = self.head;
+        while let Some(cur_ptr) = cur {
+            let next = link_of::<A>(cur_ptr).next.get().map(NonNull::cast);
+            if cur_ptr == target {
+                match prev {
+                    Some(prev_ptr) => link_of::<A>(prev_ptr).next.set(next.map(NonNull::cast)),
+                    None => self.head = next,
+                }
+                if next.is_none() {
+                    self.tail = prev;
+                }
+                self.len -= 1;
+                A::link(value).reset();
+                return true;
+            }
+            prev = cur;
+            cur = next;
+        }
+        unreachable!()
+    }
+
+    pub fn iter(&self) -> Iter<'a, A> {
+        Iter { cur: self.head, remaining: self.len, _marker: PhantomData }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<'a, A: Adapter> DoublyList<'a, A> {
+    pub fn new() -> Self {
+        Self { head: None, tail: None, len: 0, id: next_list_id(), _marker: PhantomData }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn front(&self) -> Option<Pin<&'a A::Value>> {
+        self.head.map(pinned::<A>)
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a A::Value>> {
+        self.tail.map(pinned::<A>)
+    }
+
+    // O(1), whether `value` is linked into this very list
+    pub fn contains(&self, value: &A::Value) -> bool {
+        A::link(value).owner.get() == self.id
+    }
+
+    fn claim(&self, value: Pin<&'a A::Value>) -> NonNull<A::Value> {
+        let link = A::link(value.get_ref());
+        assert!(!link.is_linked(), "Cannot link a value that is already on a list");
+        link.owner.set(self.id);
+        NonNull::from(value.get_ref())
+    }
+
+    // Links `value_ptr` between two neighbours, `None` standing for the ends of the list
+    fn link_between(&mut self, value_ptr: NonNull<A::Value>,
+                    prev: Option<NonNull<A::Value>>, next: Option<NonNull<A::Value>>) {
+        let link = link_of::<A>(value_ptr);
+        link.prev.set(prev.map(NonNull::cast));
+        link.next.set(next.map(NonNull::cast));
+        match prev {
+            Some(prev_ptr) => link_of::<A>(prev_ptr).next.set(Some(value_ptr.cast())),
+            None => self.head = Some(value_ptr),
+        }
+        match next {
+            Some(next_ptr) => link_of::<A>(next_ptr).prev.set(Some(value_ptr.cast())),
+            None => self.tail = Some(value_ptr),
+        }
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, value_ptr: NonNull<A::Value>) -> Pin<&'a A::Value> {
+        let link = link_of::<A>(value_ptr);
+        let prev = link.prev.get().map(NonNull::cast::<A::Value>);
+        let next = link.next.get().map(NonNull::cast::<A::Value>);
+        match prev {
+            Some(prev_ptr) => link_of::<A>(prev_ptr).next.set(next.map(NonNull::cast)),
+            None => self.head = next,
+        }
+        match next {
+            Some(next_ptr) => link_of::<A>(next_ptr).prev.set(prev.map(NonNull::cast)),
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        link.reset();
+        pinned::<A>(value_ptr)
+    }
+
+    pub fn push_front(&mut self, value: Pin<&'a A::Value>) {
+        let value_ptr = self.claim(value);
+        self.link_between(value_ptr, None, self.head);
+    }
+
+    pub fn push_back(&mut self, value: Pin<&'a A::Value>) {
+        let value_ptr = self.claim(value);
+        self.link_between(value_ptr, self.tail, None);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<&'a A::Value>> {
+        self.head.map(|head_ptr| self.unlink(head_ptr))
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<&'a A::Value>> {
+        self.tail.map(|tail_ptr| self.unlink(tail_ptr))
+    }
+
+    // O(1), unlinks `value` if it is on this list
+    pub fn remove(&mut self, value: &A::Value) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.unlink(NonNull::from(value));
+        true
+    }
+
+    pub fn insert_after(&mut self, pos: &A::Value, value: Pin<&'a A::Value>) {
+        assert!(self.contains(pos), "Cannot insert next to a value that is not on this list");
+        let pos_ptr = NonNull::from(pos);
+        let next = A::link(pos).next.get().map(NonNull::cast);
+        let value_ptr = self.claim(value);
+        self.link_between(value_ptr, Some(pos_ptr), next);
+    }
+
+    pub fn insert_before(&mut self, pos: &A::Value, value: Pin<&'a A::Value>) {
+        assert!(self.contains(pos), "Cannot insert next to a value that is not on this list");
+        let pos_ptr = NonNull::from(pos);
+        let prev = A::link(pos).prev.get().map(NonNull::cast);
+        let value_ptr = self.claim(value);
+        self.link_between(value_ptr, prev, Some(pos_ptr));
+    }
+
+    pub fn iter(&self) -> Iter<'a, A> {
+        Iter { cur: self.head, remaining: self.len, _marker: PhantomData }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<'a, A: Adapter> Drop for SinglyList<'a, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, A: Adapter> Drop for DoublyList<'a, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, A: Adapter> IntoIterator for &SinglyList<'a, A> {
+    type Item = Pin<&'a A::Value>;
+    type IntoIter = Iter<'a, A>;
+
+    fn into_iter(self) -> Iter<'a, A> {
+        self.iter()
+    }
+}
+
+impl<'a, A: Adapter> IntoIterator for &DoublyList<'a, A> {
+    type Item = Pin<&'a A::Value>;
+    type IntoIter = Iter<'a, A>;
+
+    fn into_iter(self) -> Iter<'a, A> {
+        self.iter()
+    }
+}
+
+impl<'a, A: Adapter> fmt::Debug for SinglyList<'a, A> where A::Value: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<'a, A: Adapter> fmt::Debug for DoublyList<'a, A> where A::Value: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<'a, A: Adapter> Default for SinglyList<'a, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, A: Adapter> Default for DoublyList<'a, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, A: Adapter> Iterator for Iter<'a, A> {
+    type Item = Pin<&'a A::Value>;
+
+    fn next(&mut self) -> Option<Pin<&'a A::Value>> {
+        self.cur.map(|cur_ptr| {
+            self.cur = link_of::<A>(cur_ptr).next.get().map(NonNull::cast);
+            self.remaining -= 1;
+            pinned::<A>(cur_ptr)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, A: Adapter> ExactSizeIterator for Iter<'a, A> {}
+
+impl<'a, A: Adapter> FusedIterator for Iter<'a, A> {}
+
+impl<'a, A: Adapter> Clone for Iter<'a, A> {
+    fn clone(&self) -> Self {
+        Iter { cur: self.cur, remaining: self.remaining, _marker: PhantomData }
+    }
+}
+
+impl<'a, A: Adapter> fmt::Debug for Iter<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+
+    #[derive(Default)]
+    struct Timer {
+        deadline: Cell<u32>,
+        wheel_link: Link,
+        ready_link: Link,
+    }
+
+    impl fmt::Debug for Timer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.deadline.get())
+        }
+    }
+
+    intrusive_adapter!(WheelAdapter = Timer { wheel_link });
+    intrusive_adapter!(ReadyAdapter = Timer { ready_link });
+
+    fn timer(deadline: u32) -> Timer {
+        Timer { deadline: Cell::new(deadline), ..Timer::default() }
+    }
+
+    fn deadlines<'a, I: Iterator<Item = Pin<&'a Timer>>>(iter: I) -> Vec<u32> {
+        iter.map(|timer| timer.deadline.get()).collect()
+    }
+
+    #[test]
+    fn singly_list() {
+        let a = pin!(timer(1));
+        let b = pin!(timer(2));
+        let c = pin!(timer(3));
+        let mut list: SinglyList<WheelAdapter> = SinglyList::new();
+        list.push_back(a.as_ref());
+        list.push_front(b.as_ref());
+        list.push_back(c.as_ref());
+        assert_eq!(format!("{:?}", list), "[2, 1, 3]");
+        assert_eq!(list.len(), 3);
+        assert!(list.contains(&a) && a.wheel_link.is_linked());
+        assert!(list.remove(&c));
+        assert!(!list.remove(&c));
+        assert_eq!(list.back().map(|t| t.deadline.get()), Some(1));
+        assert_eq!(list.pop_front().map(|t| t.deadline.get()), Some(2));
+        assert!(!b.wheel_link.is_linked());
+        list.push_back(b.as_ref());
+        assert_eq!(deadlines(list.iter()), vec![1, 2]);
+        drop(list);
+        assert!(!a.wheel_link.is_linked() && !b.wheel_link.is_linked());
+    }
+
+    #[test]
+    fn doubly_list() {
+        let timers = [pin!(timer(1)), pin!(timer(2)), pin!(timer(3)), pin!(timer(4))];
+        let mut list: DoublyList<WheelAdapter> = DoublyList::new();
+        list.push_back(timers[1].as_ref());
+        list.push_front(timers[0].as_ref());
+        list.insert_after(&timers[1], timers[3].as_ref());
+        list.insert_before(&timers[3], timers[2].as_ref());
+        assert_eq!(deadlines(list.iter()), vec![1, 2, 3, 4]);
+        assert!(list.remove(&timers[2]));
+        assert!(list.remove(&timers[0]));
+        assert_eq!(deadlines(list.iter()), vec![2, 4]);
+        assert_eq!(list.pop_back().map(|t| t.deadline.get()), Some(4));
+        assert_eq!(list.front().map(|t| t.deadline.get()), Some(2));
+        assert_eq!(list.len(), 1);
+        list.clear();
+        assert!(list.is_empty() && timers.iter().all(|t| !t.wheel_link.is_linked()));
+    }
+
+    #[test]
+    fn value_on_two_lists() {
+        let a = pin!(timer(10));
+        let b = pin!(timer(20));
+        let mut wheel: DoublyList<WheelAdapter> = DoublyList::new();
+        let mut ready: SinglyList<ReadyAdapter> = SinglyList::new();
+        let mut other_wheel: DoublyList<WheelAdapter> = DoublyList::new();
+        wheel.push_back(a.as_ref());
+        wheel.push_back(b.as_ref());
+        ready.push_back(b.as_ref());
+        ready.push_back(a.as_ref());
+        b.deadline.set(25);
+        assert_eq!(deadlines(wheel.iter()), vec![10, 25]);
+        assert_eq!(deadlines(ready.iter()), vec![25, 10]);
+        // removing through the wrong list leaves both untouched
+        assert!(!other_wheel.remove(&a));
+        assert!(wheel.remove(&a));
+        other_wheel.push_back(a.as_ref());
+        assert_eq!(deadlines(ready.iter()), vec![25, 10]);
+        assert!(ready.contains(&a) && other_wheel.contains(&a) && !wheel.contains(&a));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot link a value that is already on a list")]
+    fn link_twice() {
+        let a = pin!(timer(1));
+        let mut first: SinglyList<WheelAdapter> = SinglyList::new();
+        let mut second: DoublyList<WheelAdapter> = DoublyList::new();
+        first.push_back(a.as_ref());
+        second.push_back(a.as_ref());
+    }
+}
@@ -0,0 +1,326 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{fence, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use super::hazard::{self, HazardPointer};
+
+// The Michael-Scott lock-free MPMC queue. `head` always points at a sentinel node whose
+// element has already been taken; the front element lives in the sentinel's successor.
+// Dequeued sentinels are reclaimed with hazard pointers.
+pub struct ConcurrentQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    // counts reserved slots, so it may run ahead of the linked elements for a moment
+    len: AtomicUsize,
+    capacity: Option<usize>,
+    waiters: AtomicUsize,
+    lock: Mutex<()>,
+    available: Condvar,
+    _marker: PhantomData<Box<Node<T>>>
+}
+
+struct Node<T> {
+    // initialized for every node behind the sentinel
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>
+}
+
+impl<T> Node<T> {
+    fn alloc(elem: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node { elem, next: AtomicPtr::new(ptr::null_mut()) }))
+    }
+}
+
+impl<T> ConcurrentQueue<T> {
+    pub fn new() -> Self {
+        Self::with_bound(None)
+    }
+
+    // A queue that refuses pushes while it holds `capacity` elements
+    pub fn bounded(capacity: usize) -> Self {
+        Self::with_bound(Some(capacity))
+    }
+
+    fn with_bound(capacity: Option<usize>) -> Self {
+        let sentinel = Node::alloc(MaybeUninit::uninit());
+        Self {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+            len: AtomicUsize::new(0),
+            capacity,
+            waiters: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            available: Condvar::new(),
+            _marker: PhantomData
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Hands `elem` back if the queue is bounded and full
+    pub fn push(&self, elem: T) -> Result<(), T> {
+        match self.capacity {
+            Some(capacity) => {
+                let mut len = self.len.load(Ordering::Relaxed);
+                loop {
+                    if len >= capacity {
+                        return Err(elem);
+                    }
+                    match self.len.compare_exchange_weak(len, len + 1, Ordering::AcqRel, Ordering::Relaxed) {
+                        Ok(_) => break,
+                        Err(actual) => len = actual,
+                    }
+                }
+            }
+            None => { self.len.fetch_add(1, Ordering::AcqRel); }
+        }
+        self.link(Node::alloc(MaybeUninit::new(elem)));
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap();
+            self.available.notify_one();
+        }
+        Ok(())
+    }
+
+    fn link(&self, node: *mut Node<T>) {
+        let hazard = HazardPointer::new();
+        loop {
+            let tail = hazard.protect(&self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if tail != self.tail.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                let linked = unsafe { &(*tail).next }
+                    .compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed);
+                if linked.is_ok() {
+                    let _ = self.tail.compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+                    return;
+                }
+            } else {
+                // another push linked its node but has not swung the tail yet
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let head_hazard = HazardPointer::new();
+        let next_hazard = HazardPointer::new();
+        loop {
+            let head = head_hazard.protect(&self.head);
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = next_hazard.protect(unsafe { &(*head).next });
+            // `next` is only safe to use while `head` is still the sentinel
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            if head == tail {
+                let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+            if self.head.compare_exchange(head, next, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                let elem = unsafe { ptr::read((*next).elem.as_ptr()) };
+                head_hazard.clear();
+                next_hazard.clear();
+                self.len.fetch_sub(1, Ordering::AcqRel);
+                unsafe { hazard::retire(head) };
+                return Some(elem);
+            }
+        }
+    }
+
+    // Blocks the calling thread until an element is available
+    pub fn pop_wait(&self) -> T {
+        if let Some(elem) = self.pop() {
+            return elem;
+        }
+        let mut guard = self.lock.lock().unwrap();
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let elem = loop {
+            fence(Ordering::SeqCst);
+            if let Some(elem) = self.pop() {
+                break elem;
+            }
+            guard = self.available.wait(guard).unwrap();
+        };
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+        elem
+    }
+}
+
+impl<T> Drop for ConcurrentQueue<T> {
+    fn drop(&mut self) {
+        let sentinel = unsafe { Box::from_raw(*self.head.get_mut()) };
+        let mut cur = sentinel.next.load(Ordering::Relaxed);
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = node.next.load(Ordering::Relaxed);
+            unsafe { ptr::drop_in_place(node.elem.as_mut_ptr()) };
+        }
+    }
+}
+
+impl<T> Default for ConcurrentQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ConcurrentQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConcurrentQueue")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+unsafe impl<T: Send> Send for ConcurrentQueue<T> {}
+
+unsafe impl<T: Send> Sync for ConcurrentQueue<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn fifo_order() {
+        let queue = ConcurrentQueue::new();
+        assert_eq!(queue.pop(), None);
+        for i in 0..5 {
+            queue.push(i).unwrap();
+        }
+        assert_eq!(queue.len(), 5);
+        assert_eq!((0..5).map(|_| queue.pop().unwrap()).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+        queue.push(5).unwrap();
+        assert_eq!(queue.pop_wait(), 5);
+    }
+
+    #[test]
+    fn bounded_capacity() {
+        let queue = ConcurrentQueue::bounded(2);
+        assert_eq!(queue.capacity(), Some(2));
+        assert_eq!(queue.push(1), Ok(()));
+        assert_eq!(queue.push(2), Ok(()));
+        assert_eq!(queue.push(3), Err(3));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.push(3), Ok(()));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(ConcurrentQueue::<u32>::bounded(0).push(1), Err(1));
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let counter = Arc::new(());
+        let queue = ConcurrentQueue::new();
+        for _ in 0..10 {
+            queue.push(counter.clone()).unwrap();
+        }
+        queue.pop();
+        assert_eq!(Arc::strong_count(&counter), 10);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn pop_wait_blocks_until_push() {
+        let queue = Arc::new(ConcurrentQueue::new());
+        let consumers: Vec<_> = (0..4).map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || queue.pop_wait())
+        }).collect();
+        thread::sleep(Duration::from_millis(20));
+        for i in 0..4 {
+            queue.push(i).unwrap();
+        }
+        let mut got: Vec<u32> = consumers.into_iter().map(|h| h.join().unwrap()).collect();
+        got.sort_unstable();
+        assert_eq!(got, vec![0, 1, 2, 3]);
+    }
+
+    // Every consumer must see each producer's elements in the order they were pushed,
+    // every element must come out exactly once, and a bounded queue must never overfill.
+    fn check_linearizable(queue: ConcurrentQueue<(usize, usize)>) {
+        let producers = 4;
+        let consumers = 4;
+        let per_producer = 20000;
+        let queue = Arc::new(queue);
+        let done = Arc::new(AtomicBool::new(false));
+        let producer_handles: Vec<_> = (0..producers).map(|p| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for seq in 0..per_producer {
+                    let mut elem = (p, seq);
+                    while let Err(back) = queue.push(elem) {
+                        elem = back;
+                        thread::yield_now();
+                    }
+                }
+            })
+        }).collect();
+        let consumer_handles: Vec<_> = (0..consumers).map(|_| {
+            let queue = queue.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut last = vec![None; producers];
+                let mut seen = Vec::new();
+                loop {
+                    if let Some(capacity) = queue.capacity() {
+                        assert!(queue.len() <= capacity);
+                    }
+                    match queue.pop() {
+                        Some((p, seq)) => {
+                            assert!(last[p] < Some(seq), "producer {} reordered", p);
+                            last[p] = Some(seq);
+                            seen.push((p, seq));
+                        }
+                        None if done.load(Ordering::Acquire) => return seen,
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        }).collect();
+        for handle in producer_handles {
+            handle.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+        let mut all: Vec<_> = consumer_handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        assert_eq!(queue.pop(), None);
+        all.sort_unstable();
+        let expected: Vec<_> = (0..producers).flat_map(|p| (0..per_producer).map(move |seq| (p, seq))).collect();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn stress_unbounded() {
+        check_linearizable(ConcurrentQueue::new());
+    }
+
+    #[test]
+    fn stress_bounded() {
+        check_linearizable(ConcurrentQueue::bounded(64));
+    }
+}
@@ -0,0 +1,305 @@
+use core::cmp::Ordering;
+use core::hash::{Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// Immutable singly linked lists whose nodes are shared between versions:
+// `push_front` returns a new list pointing at the old one, and `clone` is O(1).
+// `PersistentList` shares nodes with `Rc`, `ArcPersistentList` with `Arc` across threads.
+macro_rules! persistent_list {
+    ($ll_name: ident, $node_name: ident, $iter_name: ident, $ptr: ident) => {
+
+pub struct $ll_name<T> {
+    head: Option<$ptr<$node_name<T>>>,
+    len: usize,
+}
+
+struct $node_name<T> {
+    elem: T,
+    next: Option<$ptr<$node_name<T>>>,
+}
+
+pub struct $iter_name<'a, T: 'a> {
+    cur: Option<&'a $node_name<T>>,
+    remaining: usize,
+}
+
+impl<T> $ll_name<T> {
+    pub fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    // A new list with `elem` in front of this one, sharing all of this one's nodes
+    pub fn push_front(&self, elem: T) -> Self {
+        let node = $ptr::new($node_name { elem, next: self.head.clone() });
+        Self { head: Some(node), len: self.len + 1 }
+    }
+
+    // The list without its first element, sharing all of its nodes
+    pub fn tail(&self) -> Self {
+        match &self.head {
+            Some(node) => Self { head: node.next.clone(), len: self.len - 1 },
+            None => Self::new(),
+        }
+    }
+
+    pub fn split_first(&self) -> Option<(&T, Self)> {
+        self.head.as_ref().map(|node| (&node.elem, self.tail()))
+    }
+
+    pub fn iter(&self) -> $iter_name<'_, T> {
+        $iter_name { cur: self.head.as_deref(), remaining: self.len }
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        self.iter().any(|elem| elem == value)
+    }
+
+    // Whether both lists start at the very same node
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => $ptr::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn reverse(&self) -> Self
+    where T: Clone
+    {
+        let mut ans = Self::new();
+        for elem in self.iter() {
+            ans = ans.push_front(elem.clone());
+        }
+        ans
+    }
+}
+
+impl<T> Drop for $ll_name<T> {
+    fn drop(&mut self) {
+        // unlink nodes one by one for as long as nobody else shares them,
+        // so that dropping a long chain does not recurse. `into_inner` hands the node to
+        // exactly one of several lists dropped at the same time, unlike `try_unwrap`,
+        // which can fail for all of them and leave the last reference to a recursive drop.
+        let mut cur = self.head.take();
+        while let Some(node) = cur {
+            match $ptr::into_inner(node) {
+                Some(mut node) => cur = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T> Clone for $ll_name<T> {
+    fn clone(&self) -> Self {
+        Self { head: self.head.clone(), len: self.len }
+    }
+}
+
+impl<T> Default for $ll_name<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for $ll_name<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        let mut list = Self::new();
+        for elem in elems.into_iter().rev() {
+            list = list.push_front(elem);
+        }
+        list
+    }
+}
+
+impl<'a, T> IntoIterator for &'a $ll_name<T> {
+    type Item = &'a T;
+    type IntoIter = $iter_name<'a, T>;
+
+    fn into_iter(self) -> $iter_name<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for $ll_name<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Hash> Hash for $ll_name<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for $ll_name<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for $ll_name<T> {}
+
+impl<T: PartialOrd> PartialOrd for $ll_name<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for $ll_name<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<'a, T> Iterator for $iter_name<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.cur.map(|node| {
+            self.cur = node.next.as_deref();
+            self.remaining -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for $iter_name<'a, T> {}
+
+impl<'a, T> FusedIterator for $iter_name<'a, T> {}
+
+impl<'a, T> Clone for $iter_name<'a, T> {
+    fn clone(&self) -> Self {
+        $iter_name { cur: self.cur, remaining: self.remaining }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for $iter_name<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple(stringify!($iter_name)).finish()
+    }
+}
+    };
+}
+
+persistent_list!(PersistentList, RcNode, Iter, Rc);
+persistent_list!(ArcPersistentList, ArcNode, ArcIter, Arc);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn structural_sharing() {
+        let base: PersistentList<u32> = (1..=3).collect();
+        let a = base.push_front(10);
+        let b = base.push_front(20);
+        assert_eq!(a.iter().cloned().collect::<Vec<_>>(), vec![10, 1, 2, 3]);
+        assert_eq!(b.iter().cloned().collect::<Vec<_>>(), vec![20, 1, 2, 3]);
+        assert!(a.tail().ptr_eq(&base));
+        assert!(b.tail().ptr_eq(&a.tail()));
+        assert!(base.clone().ptr_eq(&base));
+        assert_eq!(a.len(), 4);
+        assert_eq!(a.split_first().map(|(x, rest)| (*x, rest.len())), Some((10, 3)));
+        drop(base);
+        assert_eq!(a.tail(), b.tail());
+        assert!(a.contains(&3));
+        assert_eq!(format!("{:?}", a.reverse()), "[3, 2, 1, 10]");
+        let empty: PersistentList<u32> = PersistentList::new();
+        assert!(empty.tail().is_empty());
+        assert!(empty.ptr_eq(&PersistentList::new()));
+        assert_eq!(empty.split_first(), None);
+    }
+
+    #[test]
+    fn drops_long_chains_iteratively() {
+        let mut list = PersistentList::new();
+        for i in 0..1_000_000 {
+            list = list.push_front(i);
+        }
+        let shared = list.tail().tail();
+        drop(list);
+        assert_eq!(shared.len(), 999_998);
+        drop(shared);
+        let list: ArcPersistentList<u32> = (0..1_000_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn drops_unshared_nodes_only() {
+        let counter = Rc::new(());
+        let base: PersistentList<Rc<()>> = (0..5).map(|_| counter.clone()).collect();
+        let longer = base.push_front(counter.clone());
+        assert_eq!(Rc::strong_count(&counter), 7);
+        drop(longer);
+        assert_eq!(Rc::strong_count(&counter), 6);
+        drop(base);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    // Lists sharing one long chain are dropped at the same time on threads whose stacks are
+    // far too small for a recursive drop of the chain
+    #[test]
+    fn concurrent_drops_of_a_shared_chain() {
+        use std::sync::Barrier;
+        let threads = 8;
+        for _ in 0..20 {
+            let list: ArcPersistentList<u32> = (0..200_000).collect();
+            let barrier = Arc::new(Barrier::new(threads));
+            let handles: Vec<_> = (0..threads).map(|_| {
+                let list = list.clone();
+                let barrier = barrier.clone();
+                thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+                    barrier.wait();
+                    drop(list);
+                }).unwrap()
+            }).collect();
+            drop(list);
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn arc_shared_across_threads() {
+        let base: ArcPersistentList<u32> = (0..100).collect();
+        let handles: Vec<_> = (0..4).map(|i| {
+            let base = base.clone();
+            thread::spawn(move || {
+                let mine = base.push_front(1000 + i);
+                assert!(mine.tail().ptr_eq(&base));
+                mine.iter().sum::<u32>()
+            })
+        }).collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), 4950 + 1000 + i as u32);
+        }
+    }
+}
@@ -2,12 +2,16 @@ use core::cmp::Ordering;
 use core::hash::{Hasher, Hash};
 use core::iter::{FromIterator, FusedIterator};
 use core::fmt;
-use core::ptr::NonNull;
+use core::ptr::{self, NonNull};
 use core::marker::PhantomData;
 use core::mem;
+use core::ops::{Index, IndexMut};
 
 pub struct SinglyLinkedList<T> {
     head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    pool: NodePool<T>,
     _marker: PhantomData<Box<Node<T>>>
 }
 
@@ -16,6 +20,14 @@ struct Node<T> {
     next: Option<NonNull<Node<T>>>
 }
 
+// Freed nodes kept for reuse, chained through their `next` fields. Their elements have
+// already been moved out. Pooling is off while `limit` is 0.
+struct NodePool<T> {
+    free: Option<NonNull<Node<T>>>,
+    len: usize,
+    limit: usize,
+}
+
 #[derive(Clone)]
 pub struct Iter<'a, T: 'a> {
     cur: Option<NonNull<Node<T>>>,
@@ -32,26 +44,99 @@ pub struct IntoIter<T> {
     list: SinglyLinkedList<T>
 }
 
+// A cursor points at an element or at the "ghost" non-element between the tail and the head.
+// The ghost's index is `len`.
+pub struct Cursor<'a, T: 'a> {
+    index: usize,
+    current: Option<NonNull<Node<T>>>,
+    list: &'a SinglyLinkedList<T>,
+}
+
+pub struct CursorMut<'a, T: 'a> {
+    index: usize,
+    current: Option<NonNull<Node<T>>>,
+    list: &'a mut SinglyLinkedList<T>,
+}
+
+pub struct ExtractIf<'a, T: 'a, F> 
+where 
+    F: FnMut(&mut T) -> bool 
+{
+    list: &'a mut SinglyLinkedList<T>,
+    prev: Option<NonNull<Node<T>>>,
+    cur: Option<NonNull<Node<T>>>,
+    pred: F,
+}
+
 impl<T> SinglyLinkedList<T> {
     pub fn new() -> Self {
         Self {
             head: None,
+            tail: None,
+            len: 0,
+            pool: NodePool::new(0),
             _marker: PhantomData
         }
     }
 
+    // A list that keeps up to `limit` freed nodes around for later pushes,
+    // saving an allocation per push in push/pop heavy loops
+    pub fn with_node_pool(limit: usize) -> Self {
+        let mut list = Self::new();
+        list.pool.limit = limit;
+        list
+    }
+
+    pub fn pool_limit(&self) -> usize {
+        self.pool.limit
+    }
+
+    // Frees pooled nodes beyond the new limit
+    pub fn set_pool_limit(&mut self, limit: usize) {
+        self.pool.limit = limit;
+        self.pool.shrink_to(limit);
+    }
+
+    // Number of freed nodes waiting for reuse
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.len
+    }
+
+    // Frees every pooled node, the limit stays as it is
+    pub fn shrink_to_fit(&mut self) {
+        self.pool.shrink_to(0);
+    }
+
+    fn alloc_node(&mut self, elem: T, next: Option<NonNull<Node<T>>>) -> NonNull<Node<T>> {
+        let node = Node { elem, next };
+        match self.pool.take() {
+            Some(node_ptr) => {
+                unsafe { ptr::write(node_ptr.as_ptr(), node) };
+                node_ptr
+            },
+            None => NonNull::from(Box::leak(Box::new(node))),
+        }
+    }
+
+    // Takes the node out of `node_ptr`, which must already be unlinked
+    fn free_node(&mut self, node_ptr: NonNull<Node<T>>) -> Node<T> {
+        let node = unsafe { ptr::read(node_ptr.as_ptr()) };
+        unsafe { self.pool.give(node_ptr) };
+        node
+    }
+
     pub fn is_empty(&self) -> bool {
         self.head.is_none()
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             cur: self.head,
             _marker: PhantomData,
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             cur: self.head,
             _marker: PhantomData,
@@ -65,8 +150,8 @@ impl<T> SinglyLinkedList<T> {
     }
     
     pub fn clear(&mut self) {
-        *self = Self::new()
-        // drop(self) is called
+        // pops one by one so that the nodes go to the pool
+        while self.pop_front().is_some() {}
     }
 }
 
@@ -81,6 +166,7 @@ impl<T> Drop for SinglyLinkedList<T> {
     }
 }
 
+// O(1) operations
 impl<T> SinglyLinkedList<T> {
     pub fn front(&self) -> Option<&T> {
         self.head.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
@@ -90,123 +176,339 @@ impl<T> SinglyLinkedList<T> {
         self.head.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
     }
 
+    pub fn back(&self) -> Option<&T> {
+        self.tail.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor { index: 0, current: self.head, list: self }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: 0, current: self.head, list: self }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor { index: self.len.saturating_sub(1), current: self.tail, list: self }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: self.len.saturating_sub(1), current: self.tail, list: self }
+    }
+
     pub fn push_front(&mut self, elem: T) {
-        let node = Box::new(Node { elem, next: self.head });
-        self.head = NonNull::new(Box::into_raw(node));
+        self.head = Some(self.alloc_node(elem, self.head));
+        if self.tail.is_none() {
+            self.tail = self.head;
+        }
+        self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
         self.head.map(|head_ptr| {
-            let node = unsafe { Box::from_raw(head_ptr.as_ptr()) };
+            let node = self.free_node(head_ptr);
             self.head = node.next;
+            if self.head.is_none() {
+                self.tail = None;
+            }
+            self.len -= 1;
             node.elem
         })
     }
-}
 
-impl<T> SinglyLinkedList<T> {
-    fn tail(&self) -> Option<NonNull<Node<T>>> {
-        let mut cur = self.head;
-        let mut ans = None;
-        while let Some(node_ptr) = cur {
-            ans = cur;
-            cur = unsafe { node_ptr.as_ref() }.next;
+    pub fn push_back(&mut self, elem: T) {
+        let new_tail_ptr = Some(self.alloc_node(elem, None));
+        if let Some(mut tail_ptr) = self.tail {
+            unsafe { tail_ptr.as_mut() }.next = new_tail_ptr;
+        } else {
+            self.head = new_tail_ptr;
         }
-        ans
+        self.tail = new_tail_ptr;
+        self.len += 1;
     }
 
-    pub fn len(&self) -> usize {
-        let mut cur = self.head;
-        let mut ans = 0;
-        while let Some(node_ptr) = cur {
-            ans += 1;
-            cur = unsafe { node_ptr.as_ref() }.next;
-        }
-        ans
-    }
-    
     pub fn append(&mut self, other: &mut Self) {
-        if let Some(mut tail_ptr) = self.tail() {
+        if let Some(mut tail_ptr) = self.tail {
+            if other.is_empty() {
+                return;
+            }
             unsafe { tail_ptr.as_mut() }.next = other.head.take();
+            self.tail = other.tail.take();
+            self.len += mem::replace(&mut other.len, 0);
         } else {
-            mem::swap(self, other)
+            // each list keeps its own pool
+            self.head = other.head.take();
+            self.tail = other.tail.take();
+            self.len = mem::replace(&mut other.len, 0);
         }
     }
+}
 
-    pub fn back(&self) -> Option<&T> {
-        self.tail().as_ref().map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.elem)
+// O(n) operations
+impl<T> SinglyLinkedList<T> {
+    // Node at position `at`, which must be less than `self.len`
+    fn node_at(&self, at: usize) -> NonNull<Node<T>> {
+        let mut cur = self.head.unwrap();
+        for _ in 0..at {
+            cur = unsafe { cur.as_ref() }.next.unwrap();
+        }
+        cur
     }
 
-    pub fn back_mut(&mut self) -> Option<&mut T> {
-        self.tail().as_mut().map(|node_ptr| &mut unsafe { &mut *node_ptr.as_ptr() }.elem)
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.len {
+            0 => None,
+            1 => self.pop_front(),
+            len => {
+                let mut new_tail_ptr = self.node_at(len - 2);
+                let old_tail_ptr = unsafe { new_tail_ptr.as_mut() }.next.take().unwrap();
+                self.tail = Some(new_tail_ptr);
+                self.len -= 1;
+                Some(self.free_node(old_tail_ptr).elem)
+            }
+        }
     }
 
-    pub fn push_back(&mut self, elem: T) {
-        let node = Box::new(Node { elem, next: None });
-        let new_tail_ptr = NonNull::new(Box::into_raw(node));
-        if let Some(mut tail_ptr) = self.tail() {
-            unsafe { tail_ptr.as_mut() }.next = new_tail_ptr;
-        } else {
-            self.head = new_tail_ptr;
+    pub fn split_off(&mut self, at: usize) -> SinglyLinkedList<T> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
+        if at == 0 {
+            let mut second_part = Self::new();
+            second_part.append(self);
+            return second_part
         }
+        if at == self.len {
+            return Self::new()
+        }
+        let mut node_ptr = self.node_at(at - 1);
+        let second_part = Self {
+            head: unsafe { node_ptr.as_mut() }.next.take(),
+            tail: self.tail,
+            len: self.len - at,
+            pool: NodePool::new(0),
+            _marker: PhantomData
+        };
+        self.tail = Some(node_ptr);
+        self.len = at;
+        second_part
     }
+}
 
-    pub fn pop_back(&mut self) -> Option<T> {
-        let (mut new_tail, mut old_tail) = (None, None);
-        let mut cur = self.head;
-        while let Some(node_ptr) = cur {
-            if let Some(next_ptr) = unsafe { node_ptr.as_ref() }.next {
-                new_tail = cur;
-                old_tail = Some(next_ptr);
-            } else {
-                break;
-            }
-            cur = unsafe { node_ptr.as_ref() }.next;
+// Positional access, O(i)
+impl<T> SinglyLinkedList<T> {
+    pub fn get(&self, at: usize) -> Option<&T> {
+        if at >= self.len {
+            return None;
         }
-        match (new_tail, old_tail) {
-            (None, None) => None,
-            (Some(node_ptr), None) => {
-                let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
-                self.head = None;
-                Some(node.elem)
-            },
-            (Some(mut new_tail_ptr), Some(old_tail_ptr)) => {
-                unsafe { new_tail_ptr.as_mut() }.next = None;
-                let node = unsafe { Box::from_raw(old_tail_ptr.as_ptr()) };
-                Some(node.elem)
-            }
-            _ => unreachable!()
+        Some(&unsafe { &*self.node_at(at).as_ptr() }.elem)
+    }
+
+    pub fn get_mut(&mut self, at: usize) -> Option<&mut T> {
+        if at >= self.len {
+            return None;
         }
+        Some(&mut unsafe { &mut *self.node_at(at).as_ptr() }.elem)
     }
 
-    pub fn split_off(&mut self, at: usize) -> SinglyLinkedList<T> {
+    pub fn insert(&mut self, at: usize, elem: T) {
+        assert!(at <= self.len, "Cannot insert at a nonexistent index");
+        if at == 0 {
+            return self.push_front(elem);
+        }
+        if at == self.len {
+            return self.push_back(elem);
+        }
+        let mut prev_ptr = self.node_at(at - 1);
+        let node_ptr = self.alloc_node(elem, unsafe { prev_ptr.as_ref() }.next);
+        unsafe { prev_ptr.as_mut() }.next = Some(node_ptr);
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, at: usize) -> T {
+        assert!(at < self.len, "Cannot remove a nonexistent index");
         if at == 0 {
-            return mem::replace(self, Self::new())
+            return self.pop_front().unwrap();
         }
+        let mut prev_ptr = self.node_at(at - 1);
+        let node_ptr = unsafe { prev_ptr.as_ref() }.next.unwrap();
+        let node = self.free_node(node_ptr);
+        unsafe { prev_ptr.as_mut() }.next = node.next;
+        if node.next.is_none() {
+            self.tail = Some(prev_ptr);
+        }
+        self.len -= 1;
+        node.elem
+    }
+
+    // Exchanges the nodes at `a` and `b` by relinking them; the elements are not moved
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(a < self.len && b < self.len, "Cannot swap a nonexistent index");
+        let (i, j) = if a < b { (a, b) } else { (b, a) };
+        if i == j {
+            return;
+        }
+        let prev_i = if i == 0 { None } else { Some(self.node_at(i - 1)) };
+        let mut node_i = match prev_i {
+            Some(prev_ptr) => unsafe { prev_ptr.as_ref() }.next.unwrap(),
+            None => self.head.unwrap(),
+        };
+        let mut prev_j = node_i;
+        for _ in i + 1..j {
+            prev_j = unsafe { prev_j.as_ref() }.next.unwrap();
+        }
+        let mut node_j = unsafe { prev_j.as_ref() }.next.unwrap();
+        match prev_i {
+            Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = Some(node_j),
+            None => self.head = Some(node_j),
+        }
+        if prev_j == node_i {
+            // adjacent nodes: i -> j becomes j -> i
+            unsafe { node_i.as_mut() }.next = unsafe { node_j.as_ref() }.next;
+            unsafe { node_j.as_mut() }.next = Some(node_i);
+        } else {
+            unsafe { prev_j.as_mut() }.next = Some(node_i);
+            mem::swap(&mut unsafe { node_i.as_mut() }.next, &mut unsafe { node_j.as_mut() }.next);
+        }
+        if self.tail == Some(node_j) {
+            self.tail = Some(node_i);
+        }
+    }
+}
+
+// In-place filtering, none of them reallocates a node
+impl<T> SinglyLinkedList<T> {
+    pub fn reverse(&mut self) {
+        let mut prev = None;
         let mut cur = self.head;
-        let mut next_id = 0;
-        while let Some(node_ptr) = cur {
-            next_id += 1;
-            if next_id == at {
-                break;
+        while let Some(mut node_ptr) = cur {
+            let node = unsafe { node_ptr.as_mut() };
+            cur = mem::replace(&mut node.next, prev);
+            prev = Some(node_ptr);
+        }
+        self.tail = self.head;
+        self.head = prev;
+    }
+
+    // Lazily unlinks and yields the elements matching `pred`; 
+    // elements not reached before the iterator is dropped are kept
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where 
+        F: FnMut(&mut T) -> bool 
+    {
+        let cur = self.head;
+        ExtractIf { list: self, prev: None, cur, pred }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where 
+        F: FnMut(&T) -> bool 
+    {
+        self.retain_mut(|elem| f(elem))
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where 
+        F: FnMut(&mut T) -> bool 
+    {
+        self.extract_if(|elem| !f(elem)).for_each(drop)
+    }
+
+    pub fn remove_first(&mut self, value: &T) -> Option<T>
+    where T: PartialEq<T> 
+    {
+        self.extract_if(|elem| *elem == *value).next()
+    }
+
+    // Removes consecutive elements for which `same_bucket(elem, previous_kept_elem)` holds
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where 
+        F: FnMut(&mut T, &mut T) -> bool 
+    {
+        let mut kept = match self.head {
+            Some(head_ptr) => head_ptr,
+            None => return,
+        };
+        while let Some(cur_ptr) = unsafe { kept.as_ref() }.next {
+            let (kept_node, cur_node) = unsafe { (&mut *kept.as_ptr(), &mut *cur_ptr.as_ptr()) };
+            if same_bucket(&mut cur_node.elem, &mut kept_node.elem) {
+                kept_node.next = cur_node.next;
+                if cur_node.next.is_none() {
+                    // before the element is dropped, which may panic
+                    self.tail = Some(kept);
+                }
+                self.len -= 1;
+                drop(self.free_node(cur_ptr));
+            } else {
+                kept = cur_ptr;
             }
-            cur = unsafe { node_ptr.as_ref() }.next;
-        }
-        assert!(at <= next_id, "Cannot split off a nonexistent index");
-        if let Some(mut node_ptr) = cur {
-            let second_part = Self {
-                head: unsafe { node_ptr.as_ref() }.next,
-                _marker: PhantomData
-            };
-            unsafe { node_ptr.as_mut() }.next = None;
-            second_part
+        }
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where 
+        K: PartialEq<K>, 
+        F: FnMut(&mut T) -> K 
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    pub fn dedup(&mut self)
+    where T: PartialEq<T> 
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+impl<T> NodePool<T> {
+    fn new(limit: usize) -> Self {
+        Self { free: None, len: 0, limit }
+    }
+
+    fn take(&mut self) -> Option<NonNull<Node<T>>> {
+        let node_ptr = self.free?;
+        self.free = unsafe { node_ptr.as_ref() }.next;
+        self.len -= 1;
+        Some(node_ptr)
+    }
+
+    // `node_ptr` must be unlinked and its element moved out
+    unsafe fn give(&mut self, node_ptr: NonNull<Node<T>>) {
+        if self.len < self.limit {
+            unsafe { ptr::addr_of_mut!((*node_ptr.as_ptr()).next).write(self.free) };
+            self.free = Some(node_ptr);
+            self.len += 1;
         } else {
-            Self::new()
+            unsafe { dealloc_node(node_ptr) };
+        }
+    }
+
+    fn shrink_to(&mut self, limit: usize) {
+        while self.len > limit {
+            let node_ptr = self.take().unwrap();
+            unsafe { dealloc_node(node_ptr) };
         }
     }
 }
 
-impl<'a, T> IntoIterator for SinglyLinkedList<T> {
+impl<T> Drop for NodePool<T> {
+    fn drop(&mut self) {
+        self.shrink_to(0);
+    }
+}
+
+// Frees the memory of a node whose element has been moved out
+unsafe fn dealloc_node<T>(node_ptr: NonNull<Node<T>>) {
+    drop(unsafe { Box::from_raw(node_ptr.as_ptr() as *mut mem::MaybeUninit<Node<T>>) });
+}
+
+impl<T> IntoIterator for SinglyLinkedList<T> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
@@ -251,10 +553,6 @@ impl<T: PartialEq> PartialEq for SinglyLinkedList<T> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().eq(other)
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.iter().ne(other)
-    }
 }
 
 impl<T: Eq> Eq for SinglyLinkedList<T> {}
@@ -271,18 +569,24 @@ impl<T: Ord> Ord for SinglyLinkedList<T> {
     }
 }
 
+impl<T> Index<usize> for SinglyLinkedList<T> {
+    type Output = T;
+
+    fn index(&self, at: usize) -> &T {
+        self.get(at).expect("Cannot access a nonexistent index")
+    }
+}
+
+impl<T> IndexMut<usize> for SinglyLinkedList<T> {
+    fn index_mut(&mut self, at: usize) -> &mut T {
+        self.get_mut(at).expect("Cannot access a nonexistent index")
+    }
+}
+
 impl<T> Extend<T> for SinglyLinkedList<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        let mut cur = self.tail();
         for elem in iter {
-            let node = Box::new(Node { elem, next: None });
-            let new_tail_ptr = NonNull::new(Box::into_raw(node));
-            if let Some(mut tail_ptr) = cur {
-                unsafe { tail_ptr.as_mut() }.next = new_tail_ptr;
-            } else {
-                self.head = new_tail_ptr;
-            }
-            cur = new_tail_ptr;
+            self.push_back(elem);
         }
     }
 }
@@ -371,6 +675,627 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<T> {
         self.list.pop_front()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 impl<T> FusedIterator for IntoIter<T> {}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            },
+            Some(cur_ptr) => {
+                self.current = unsafe { cur_ptr.as_ref() }.next;
+                self.index += 1;
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|cur_ptr| &unsafe { &*cur_ptr.as_ptr() }.elem)
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.current {
+            None => self.list.head,
+            Some(cur_ptr) => unsafe { cur_ptr.as_ref() }.next,
+        };
+        next.map(|next_ptr| &unsafe { &*next_ptr.as_ptr() }.elem)
+    }
+}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor { index: self.index, current: self.current, list: self.list }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Cursor<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cursor").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            },
+            Some(cur_ptr) => {
+                self.current = unsafe { cur_ptr.as_ref() }.next;
+                self.index += 1;
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|cur_ptr| &mut unsafe { &mut *cur_ptr.as_ptr() }.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            None => self.list.head,
+            Some(cur_ptr) => unsafe { cur_ptr.as_ref() }.next,
+        };
+        next.map(|next_ptr| &mut unsafe { &mut *next_ptr.as_ptr() }.elem)
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor { index: self.index, current: self.current, list: self.list }
+    }
+
+    // Inserts after the current element, or at the front when at the ghost
+    pub fn insert_after(&mut self, elem: T) {
+        match self.current {
+            None => {
+                self.list.push_front(elem);
+                self.index = self.list.len;
+            },
+            Some(mut cur_ptr) => {
+                let node_ptr = Some(self.list.alloc_node(elem, unsafe { cur_ptr.as_ref() }.next));
+                unsafe { cur_ptr.as_mut() }.next = node_ptr;
+                if self.list.tail == self.current {
+                    self.list.tail = node_ptr;
+                }
+                self.list.len += 1;
+            }
+        }
+    }
+
+    // Removes the element after the current one, or the front when at the ghost
+    pub fn remove_next(&mut self) -> Option<T> {
+        match self.current {
+            None => {
+                let ans = self.list.pop_front();
+                self.index = self.list.len;
+                ans
+            },
+            Some(mut cur_ptr) => {
+                let next_ptr = unsafe { cur_ptr.as_ref() }.next?;
+                let node = self.list.free_node(next_ptr);
+                unsafe { cur_ptr.as_mut() }.next = node.next;
+                if node.next.is_none() {
+                    self.list.tail = self.current;
+                }
+                self.list.len -= 1;
+                Some(node.elem)
+            }
+        }
+    }
+
+    // Splits off everything after the current element, or the whole list when at the ghost
+    pub fn split_after(&mut self) -> SinglyLinkedList<T> {
+        match self.current {
+            None => {
+                self.index = 0;
+                self.list.split_off(0)
+            },
+            Some(mut cur_ptr) => {
+                let head = unsafe { cur_ptr.as_mut() }.next.take();
+                if head.is_none() {
+                    return SinglyLinkedList::new();
+                }
+                let second_part = SinglyLinkedList {
+                    head,
+                    tail: self.list.tail,
+                    len: self.list.len - self.index - 1,
+                    pool: NodePool::new(0),
+                    _marker: PhantomData
+                };
+                self.list.tail = self.current;
+                self.list.len = self.index + 1;
+                second_part
+            }
+        }
+    }
+
+    // Moves all of `other` after the current element, or to the front when at the ghost
+    pub fn splice_after(&mut self, mut other: SinglyLinkedList<T>) {
+        let mut other_tail_ptr = match other.tail {
+            Some(tail_ptr) => tail_ptr,
+            None => return,
+        };
+        match self.current {
+            None => {
+                other.append(self.list);
+                self.list.append(&mut other);
+                self.index = self.list.len;
+            },
+            Some(mut cur_ptr) => {
+                let next = unsafe { cur_ptr.as_ref() }.next;
+                unsafe { other_tail_ptr.as_mut() }.next = next;
+                unsafe { cur_ptr.as_mut() }.next = other.head.take();
+                if next.is_none() {
+                    self.list.tail = Some(other_tail_ptr);
+                }
+                self.list.len += mem::replace(&mut other.len, 0);
+                other.tail = None;
+            }
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CursorMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+impl<'a, T, F> Iterator for ExtractIf<'a, T, F> 
+where 
+    F: FnMut(&mut T) -> bool 
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(cur_ptr) = self.cur {
+            let node = unsafe { &mut *cur_ptr.as_ptr() };
+            self.cur = node.next;
+            if (self.pred)(&mut node.elem) {
+                match self.prev {
+                    Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = node.next,
+                    None => self.list.head = node.next,
+                }
+                if node.next.is_none() {
+                    self.list.tail = self.prev;
+                }
+                self.list.len -= 1;
+                return Some(self.list.free_node(cur_ptr).elem);
+            }
+            self.prev = Some(cur_ptr);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+impl<'a, T, F> FusedIterator for ExtractIf<'a, T, F> 
+where 
+    F: FnMut(&mut T) -> bool 
+{}
+
+impl<'a, T: 'a + fmt::Debug, F> fmt::Debug for ExtractIf<'a, T, F> 
+where 
+    F: FnMut(&mut T) -> bool 
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ExtractIf").field(&self.list).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Xoshiro256StarStar;
+    use std::collections::VecDeque;
+
+    impl<T> SinglyLinkedList<T> {
+        fn check_invariants(&self) {
+            let mut cur = self.head;
+            let mut last = None;
+            let mut len = 0;
+            while let Some(node_ptr) = cur {
+                len += 1;
+                last = cur;
+                cur = unsafe { node_ptr.as_ref() }.next;
+            }
+            assert_eq!(len, self.len);
+            assert_eq!(last, self.tail);
+        }
+    }
+
+    #[test]
+    fn random_operations_keep_invariants() {
+        let mut rng = Xoshiro256StarStar::new(31);
+        let mut list = SinglyLinkedList::new();
+        let mut model = VecDeque::new();
+        for step in 0..20000 {
+            match rng.gen_index(8) {
+                0 | 1 => {
+                    list.push_back(step);
+                    model.push_back(step);
+                },
+                2 => {
+                    list.push_front(step);
+                    model.push_front(step);
+                },
+                3 => assert_eq!(list.pop_front(), model.pop_front()),
+                4 => assert_eq!(list.pop_back(), model.pop_back()),
+                5 => {
+                    let at = rng.gen_index(model.len() + 1);
+                    let mut second = list.split_off(at);
+                    let second_model = model.split_off(at);
+                    second.check_invariants();
+                    assert!(second.iter().eq(second_model.iter()));
+                    second.push_back(step);
+                    list.append(&mut second);
+                    second.check_invariants();
+                    assert!(second.is_empty());
+                    model.extend(second_model);
+                    model.push_back(step);
+                },
+                6 => {
+                    let extra = rng.gen_index(4);
+                    list.extend(step..step + extra);
+                    model.extend(step..step + extra);
+                },
+                _ => {
+                    let mut other: SinglyLinkedList<usize> = (0..rng.gen_index(3)).collect();
+                    model.extend(other.iter().cloned());
+                    list.append(&mut other);
+                    other.check_invariants();
+                },
+            }
+            list.check_invariants();
+            assert_eq!(list.len(), model.len());
+            assert_eq!(list.back(), model.back());
+            assert_eq!(list.front(), model.front());
+        }
+        assert!(list.iter().eq(model.iter()));
+        assert_eq!(list.into_iter().len(), model.len());
+    }
+
+    #[test]
+    fn cursor() {
+        let list: SinglyLinkedList<u32> = (1..=3).collect();
+        let mut cursor = list.cursor_front();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (Some(0), Some(&1), Some(&2)));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (Some(2), Some(&3), None));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (None, None, Some(&1)));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(list.cursor_back().current(), Some(&3));
+        let empty: SinglyLinkedList<u32> = SinglyLinkedList::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+    }
+
+    #[test]
+    fn cursor_mut_edits() {
+        let mut list: SinglyLinkedList<u32> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_after(10);
+        *cursor.current().unwrap() = 0;
+        assert_eq!(cursor.peek_next(), Some(&mut 10));
+        cursor.move_next();
+        assert_eq!(cursor.remove_next(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.as_cursor().index(), Some(2));
+        assert_eq!(cursor.remove_next(), None);
+        cursor.insert_after(4);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(99);
+        cursor.remove_next();
+        cursor.insert_after(5);
+        list.check_invariants();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![5, 0, 10, 3, 4]);
+        assert_eq!(list.back(), Some(&4));
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_next(), None);
+        cursor.insert_after(6);
+        list.check_invariants();
+        assert_eq!(list.back(), Some(&6));
+    }
+
+    #[test]
+    fn cursor_mut_split_splice() {
+        let mut list: SinglyLinkedList<u32> = (0..6).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        let mut second = cursor.split_after();
+        cursor.splice_after((10..12).collect());
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_after(SinglyLinkedList::new());
+        cursor.splice_after((20..21).collect());
+        list.check_invariants();
+        second.check_invariants();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 10, 11, 20]);
+        assert_eq!(second.iter().cloned().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+
+        let mut cursor = second.cursor_back_mut();
+        cursor.move_next();
+        cursor.splice_after((7..9).collect());
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 7));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.split_after().len(), 3);
+        second.check_invariants();
+        assert_eq!(second.iter().cloned().collect::<Vec<_>>(), vec![7, 8, 2]);
+        let mut cursor = second.cursor_back_mut();
+        cursor.move_next();
+        assert_eq!(cursor.split_after().len(), 3);
+        assert!(second.is_empty());
+        second.check_invariants();
+    }
+
+    fn to_vec(list: &SinglyLinkedList<u32>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn reverse() {
+        for len in 0..5 {
+            let mut list: SinglyLinkedList<u32> = (0..len).collect();
+            list.reverse();
+            list.check_invariants();
+            assert_eq!(to_vec(&list), (0..len).rev().collect::<Vec<_>>());
+            list.push_back(10);
+            assert_eq!(list.back(), Some(&10));
+        }
+    }
+
+    #[test]
+    fn retain() {
+        let mut list: SinglyLinkedList<u32> = (0..10).collect();
+        list.retain(|&x| x % 3 != 0);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![1, 2, 4, 5, 7, 8]);
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x != 80
+        });
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![10, 20, 40, 50, 70]);
+        assert_eq!(list.back(), Some(&70));
+        list.retain(|_| false);
+        list.check_invariants();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn extract_if_is_lazy() {
+        let mut list: SinglyLinkedList<u32> = (0..10).collect();
+        {
+            let mut evens = list.extract_if(|x| *x % 2 == 0);
+            assert_eq!(evens.next(), Some(0));
+            assert_eq!(evens.next(), Some(2));
+        }
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![1, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(list.extract_if(|x| *x > 6).collect::<Vec<_>>(), vec![7, 8, 9]);
+        list.check_invariants();
+        assert_eq!(list.back(), Some(&6));
+        assert_eq!(list.remove_first(&4), Some(4));
+        assert_eq!(list.remove_first(&4), None);
+        assert_eq!(list.remove_first(&1), Some(1));
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![3, 5, 6]);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut list: SinglyLinkedList<u32> = vec![1, 1, 2, 3, 3, 3, 1, 4, 4].into_iter().collect();
+        list.dedup();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![1, 2, 3, 1, 4]);
+        list.dedup_by_key(|x| *x / 2);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![1, 2, 1, 4]);
+        let mut list: SinglyLinkedList<u32> = vec![5, 5, 5].into_iter().collect();
+        list.dedup();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![5]);
+        let mut empty: SinglyLinkedList<u32> = SinglyLinkedList::new();
+        empty.dedup();
+        empty.check_invariants();
+    }
+
+    #[test]
+    fn dedup_keeps_tail_when_drop_panics() {
+        struct Fragile(u32);
+
+        impl Drop for Fragile {
+            fn drop(&mut self) {
+                if self.0 == 0 && !std::thread::panicking() {
+                    panic!("dropped a duplicate");
+                }
+            }
+        }
+
+        let mut list = SinglyLinkedList::with_node_pool(4);
+        list.extend(vec![Fragile(1), Fragile(0)]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.dedup_by(|_, _| true)));
+        assert!(result.is_err());
+        list.check_invariants();
+        list.push_back(Fragile(2));
+        list.check_invariants();
+        assert_eq!(list.iter().map(|elem| elem.0).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn positional_access() {
+        let mut list: SinglyLinkedList<u32> = (0..5).collect();
+        assert_eq!((list.get(0), list.get(4), list.get(5)), (Some(&0), Some(&4), None));
+        *list.get_mut(2).unwrap() = 20;
+        list[3] += 30;
+        assert_eq!(list[3], 33);
+        list.insert(0, 100);
+        list.insert(6, 106);
+        list.insert(3, 103);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![100, 0, 1, 103, 20, 33, 4, 106]);
+        assert_eq!(list.remove(7), 106);
+        assert_eq!(list.remove(0), 100);
+        assert_eq!(list.remove(2), 103);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![0, 1, 20, 33, 4]);
+        assert_eq!(list.back(), Some(&4));
+    }
+
+    #[test]
+    fn swap_relinks_nodes() {
+        let len = 6;
+        for a in 0..len {
+            for b in 0..len {
+                let mut list: SinglyLinkedList<u32> = (0..len as u32).collect();
+                let before: Vec<*const u32> = list.iter().map(|elem| elem as *const u32).collect();
+                list.swap(a, b);
+                list.check_invariants();
+                let mut expected: Vec<u32> = (0..len as u32).collect();
+                expected.swap(a, b);
+                assert_eq!(to_vec(&list), expected);
+                let mut expected_ptrs = before.clone();
+                expected_ptrs.swap(a, b);
+                assert!(list.iter().map(|elem| elem as *const u32).eq(expected_ptrs));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot insert at a nonexistent index")]
+    fn invalid_insert() {
+        let mut list: SinglyLinkedList<u32> = (0..3).collect();
+        list.insert(4, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot remove a nonexistent index")]
+    fn invalid_remove() {
+        let mut list: SinglyLinkedList<u32> = (0..3).collect();
+        list.remove(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot swap a nonexistent index")]
+    fn invalid_swap() {
+        let mut list: SinglyLinkedList<u32> = (0..3).collect();
+        list.swap(0, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot access a nonexistent index")]
+    fn invalid_index() {
+        let list: SinglyLinkedList<u32> = (0..3).collect();
+        let _ = list[3];
+    }
+
+    #[test]
+    fn fifo_queue() {
+        let mut queue = SinglyLinkedList::new();
+        for i in 0..100000 {
+            queue.push_back(i);
+            if i % 3 == 0 {
+                queue.pop_front();
+            }
+        }
+        queue.check_invariants();
+        assert_eq!(queue.len(), 66666);
+        assert_eq!(queue.back(), Some(&99999));
+    }
+
+    #[test]
+    fn node_pool() {
+        let counter = std::rc::Rc::new(());
+        let mut list = SinglyLinkedList::with_node_pool(4);
+        assert_eq!(list.pool_limit(), 4);
+        list.extend((0..10).map(|_| counter.clone()));
+        list.clear();
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+        assert_eq!(list.pooled_nodes(), 4);
+        list.push_front(counter.clone());
+        list.push_back(counter.clone());
+        list.insert(1, counter.clone());
+        assert_eq!(list.pooled_nodes(), 1);
+        let mut second = list.split_off(0);
+        assert_eq!((list.pooled_nodes(), second.pooled_nodes()), (1, 0));
+        list.append(&mut second);
+        list.remove(1);
+        list.pop_back();
+        assert_eq!(list.pooled_nodes(), 3);
+        list.set_pool_limit(2);
+        assert_eq!(list.pooled_nodes(), 2);
+        list.shrink_to_fit();
+        assert_eq!((list.pooled_nodes(), list.pool_limit()), (0, 2));
+        list.check_invariants();
+        assert_eq!(std::rc::Rc::strong_count(&counter), 2);
+        drop(list);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn pooled_random_operations() {
+        let mut rng = Xoshiro256StarStar::new(45);
+        let mut list = SinglyLinkedList::with_node_pool(16);
+        let mut model = VecDeque::new();
+        for step in 0..20000 {
+            match rng.gen_index(6) {
+                0 | 1 => {
+                    list.push_front(step);
+                    model.push_front(step);
+                },
+                2 => {
+                    list.push_back(step);
+                    model.push_back(step);
+                },
+                3 => assert_eq!(list.pop_front(), model.pop_front()),
+                4 => assert_eq!(list.pop_back(), model.pop_back()),
+                _ => {
+                    list.retain(|x| x % 3 != 0);
+                    model.retain(|x| x % 3 != 0);
+                }
+            }
+            assert!(list.pooled_nodes() <= 16);
+        }
+        list.check_invariants();
+        assert_eq!(list.into_iter().collect::<VecDeque<_>>(), model);
+    }
+}
@@ -0,0 +1,714 @@
+use core::marker::PhantomData;
+use core::iter::FusedIterator;
+use core::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+// A singly linked list stored in a file.
+//
+// The file starts with a header, followed by fixed-size records of the form
+// `next: u64, elem: [u8; T::SIZE]`. Links are byte offsets of records in the file,
+// with 0 (the header's offset) standing for "no node". Popped records are reused by
+// later pushes: the most recently freed one is held detached in the header as the spare,
+// and the others are kept on a free list.
+//
+// Updates only write records that the committed header never reads, and then write the new
+// header. A crash before the header is written therefore leaves the previous list intact.
+// The writes go to fresh slots, to the spare record, to the tail's `next`, which is never
+// followed because lists are only walked for `len` records, or to the elements (but not the
+// links) of records on the free list.
+//
+// All reads and writes are positioned, so the file cursor is never used and shared
+// references can read concurrently.
+pub struct SinglyLinkedListDb<T: Record> {
+    file: File,
+    header: Header,
+    auto_sync: bool,
+    _marker: PhantomData<T>,
+}
+
+// Fixed-size binary encoding of the elements of a `SinglyLinkedListDb`
+pub trait Record: Sized {
+    const SIZE: usize;
+
+    fn encode(&self, buf: &mut [u8]);
+
+    fn decode(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_record {
+    ($($t: ty),*) => {
+        $(
+impl Record for $t {
+    const SIZE: usize = core::mem::size_of::<$t>();
+
+    fn encode(&self, buf: &mut [u8]) {
+        buf.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(buf: &[u8]) -> Self {
+        let mut bytes = [0u8; core::mem::size_of::<$t>()];
+        bytes.copy_from_slice(buf);
+        <$t>::from_le_bytes(bytes)
+    }
+}
+        )*
+    };
+}
+
+impl_record!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<const N: usize> Record for [u8; N] {
+    const SIZE: usize = N;
+
+    fn encode(&self, buf: &mut [u8]) {
+        buf.copy_from_slice(self);
+    }
+
+    fn decode(buf: &[u8]) -> Self {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(buf);
+        bytes
+    }
+}
+
+const MAGIC: [u8; 8] = *b"LJSLLDB2";
+const NIL: u64 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Header {
+    elem_size: u64,
+    head: u64,
+    tail: u64,
+    len: u64,
+    // a free record outside the free list, `NIL` only when the free list is empty as well
+    spare: u64,
+    free: u64,
+    slots: u64,
+}
+
+const HEADER_SIZE: u64 = 8 + 7 * 8;
+
+impl Header {
+    fn new(elem_size: usize) -> Self {
+        Self { elem_size: elem_size as u64, head: NIL, tail: NIL, len: 0, spare: NIL, free: NIL, slots: 0 }
+    }
+
+    fn encode(&self) -> [u8; HEADER_SIZE as usize] {
+        let mut buf = [0u8; HEADER_SIZE as usize];
+        buf[..8].copy_from_slice(&MAGIC);
+        let fields = [self.elem_size, self.head, self.tail, self.len, self.spare, self.free, self.slots];
+        for (i, field) in fields.iter().enumerate() {
+            buf[8 + i * 8..16 + i * 8].copy_from_slice(&field.to_le_bytes());
+        }
+        buf
+    }
+
+    fn decode(buf: &[u8; HEADER_SIZE as usize]) -> io::Result<Self> {
+        if buf[..8] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a linked list database file"));
+        }
+        let field = |i: usize| u64::decode(&buf[8 + i * 8..16 + i * 8]);
+        Ok(Self {
+            elem_size: field(0),
+            head: field(1),
+            tail: field(2),
+            len: field(3),
+            spare: field(4),
+            free: field(5),
+            slots: field(6),
+        })
+    }
+}
+
+pub struct Iter<'a, T: Record> {
+    file: &'a File,
+    cur: u64,
+    remaining: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Record> SinglyLinkedListDb<T> {
+    // Opens the list stored at `path`, creating an empty one if the file does not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        Self::from_file(file)
+    }
+
+    // Uses `file`, which must be readable and writable; an empty file becomes an empty list
+    pub fn from_file(file: File) -> io::Result<Self> {
+        let header = if file.metadata()?.len() == 0 {
+            let header = Header::new(T::SIZE);
+            write_all_at(&file, &header.encode(), 0)?;
+            file.sync_all()?;
+            header
+        } else {
+            let mut buf = [0u8; HEADER_SIZE as usize];
+            read_exact_at(&file, &mut buf, 0)?;
+            Header::decode(&buf)?
+        };
+        if header.elem_size != T::SIZE as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Record size does not match the element type"));
+        }
+        Ok(Self { file, header, auto_sync: true, _marker: PhantomData })
+    }
+
+    // When enabled (the default) every mutating operation is flushed to disk with fsync
+    // before it returns. Otherwise call `sync` to make the changes durable.
+    pub fn set_auto_sync(&mut self, auto_sync: bool) {
+        self.auto_sync = auto_sync;
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.header.len as usize
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            file: &self.file,
+            cur: self.header.head,
+            remaining: self.header.len,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn front(&self) -> io::Result<Option<T>> {
+        self.read_elem(self.header.head)
+    }
+
+    pub fn back(&self) -> io::Result<Option<T>> {
+        self.read_elem(self.header.tail)
+    }
+
+    pub fn push_front(&mut self, elem: T) -> io::Result<()> {
+        let mut header = self.header;
+        let offset = self.alloc(&mut header)?;
+        write_record(&self.file, offset, header.head, &elem)?;
+        if header.len == 0 {
+            header.tail = offset;
+        }
+        header.head = offset;
+        header.len += 1;
+        self.commit(header)
+    }
+
+    pub fn push_back(&mut self, elem: T) -> io::Result<()> {
+        let mut header = self.header;
+        let offset = self.alloc(&mut header)?;
+        write_record(&self.file, offset, NIL, &elem)?;
+        if header.len == 0 {
+            header.head = offset;
+        } else {
+            write_next(&self.file, header.tail, offset)?;
+        }
+        header.tail = offset;
+        header.len += 1;
+        self.commit(header)
+    }
+
+    pub fn pop_front(&mut self) -> io::Result<Option<T>> {
+        if self.header.len == 0 {
+            return Ok(None);
+        }
+        let mut header = self.header;
+        let (next, elem) = read_record::<T>(&self.file, header.head)?;
+        let popped = header.head;
+        if header.len == 1 {
+            header.head = NIL;
+            header.tail = NIL;
+        } else {
+            header.head = next;
+        }
+        header.len -= 1;
+        self.release(&mut header, popped)?;
+        self.commit(header)?;
+        Ok(Some(elem))
+    }
+
+    // Moves every element of `other` to the back of this list, leaving `other` empty.
+    //
+    // The records are written where the committed header of this list never reads and become
+    // reachable with a single header write, so a crash before it leaves both lists as they
+    // were. `other` is only cleared afterwards: a crash between the two header writes leaves
+    // the moved elements in both lists.
+    pub fn append(&mut self, other: &mut Self) -> io::Result<()> {
+        let n = other.header.len as usize;
+        if n == 0 {
+            return Ok(());
+        }
+        let mut header = self.header;
+        let (offsets, linked) = self.alloc_many(&mut header, n)?;
+        for (i, elem) in other.iter().enumerate() {
+            let elem = elem?;
+            if i < linked {
+                write_record(&self.file, offsets[i], offsets.get(i + 1).copied().unwrap_or(NIL), &elem)?;
+            } else {
+                write_elem(&self.file, offsets[i], &elem)?;
+            }
+        }
+        if header.len == 0 {
+            header.head = offsets[0];
+        } else {
+            write_next(&self.file, header.tail, offsets[0])?;
+        }
+        header.tail = offsets[n - 1];
+        header.len += n as u64;
+        self.commit(header)?;
+        other.clear()
+    }
+
+    // Puts every record on the free list in O(1)
+    pub fn clear(&mut self) -> io::Result<()> {
+        if self.header.len == 0 {
+            return Ok(());
+        }
+        let mut header = self.header;
+        write_next(&self.file, header.tail, header.free)?;
+        header.free = header.head;
+        if header.spare == NIL {
+            header.spare = header.free;
+            header.free = read_next(&self.file, header.spare)?;
+        }
+        header.head = NIL;
+        header.tail = NIL;
+        header.len = 0;
+        self.commit(header)
+    }
+
+    fn read_elem(&self, offset: u64) -> io::Result<Option<T>> {
+        if offset == NIL {
+            return Ok(None);
+        }
+        read_record::<T>(&self.file, offset).map(|(_, elem)| Some(elem))
+    }
+
+    // Offset of a free record, either the spare or a new one at the end of the file. The
+    // spare is refilled from the free list, so no record on the free list is ever written.
+    fn alloc(&self, header: &mut Header) -> io::Result<u64> {
+        if header.spare != NIL {
+            let offset = header.spare;
+            header.spare = header.free;
+            if header.free != NIL {
+                header.free = read_next(&self.file, header.free)?;
+            }
+            Ok(offset)
+        } else {
+            let offset = HEADER_SIZE + header.slots * record_size::<T>();
+            header.slots += 1;
+            Ok(offset)
+        }
+    }
+
+    // Offsets of `n` free records in list order: the spare, then new records at the end of
+    // the file, then records from the front of the free list. The latter are already chained
+    // in order and must keep their links, so only the links of the records before them,
+    // whose number is returned as well, may be written.
+    fn alloc_many(&self, header: &mut Header, n: usize) -> io::Result<(Vec<u64>, usize)> {
+        let mut offsets = Vec::with_capacity(n);
+        if header.spare != NIL {
+            offsets.push(header.spare);
+        }
+        let mut free = Vec::new();
+        let mut cur = header.free;
+        while offsets.len() + free.len() < n && cur != NIL {
+            free.push(cur);
+            cur = read_next(&self.file, cur)?;
+        }
+        while offsets.len() + free.len() < n {
+            offsets.push(HEADER_SIZE + header.slots * record_size::<T>());
+            header.slots += 1;
+        }
+        let linked = offsets.len();
+        offsets.append(&mut free);
+        if header.spare != NIL {
+            header.spare = cur;
+            header.free = if cur != NIL { read_next(&self.file, cur)? } else { NIL };
+        }
+        Ok((offsets, linked))
+    }
+
+    // Makes `offset` the spare, moving the previous spare onto the free list
+    fn release(&self, header: &mut Header, offset: u64) -> io::Result<()> {
+        if header.spare != NIL {
+            write_next(&self.file, header.spare, header.free)?;
+            header.free = header.spare;
+        }
+        header.spare = offset;
+        Ok(())
+    }
+
+    // Records are written before the header that makes them reachable
+    fn commit(&mut self, header: Header) -> io::Result<()> {
+        if self.auto_sync {
+            self.file.sync_data()?;
+        }
+        write_all_at(&self.file, &header.encode(), 0)?;
+        if self.auto_sync {
+            self.file.sync_data()?;
+        }
+        self.header = header;
+        Ok(())
+    }
+}
+
+fn record_size<T: Record>() -> u64 {
+    8 + T::SIZE as u64
+}
+
+fn read_next(file: &File, offset: u64) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    read_exact_at(file, &mut buf, offset)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn write_next(file: &File, offset: u64, next: u64) -> io::Result<()> {
+    write_all_at(file, &next.to_le_bytes(), offset)
+}
+
+fn read_record<T: Record>(file: &File, offset: u64) -> io::Result<(u64, T)> {
+    let mut buf = vec![0u8; record_size::<T>() as usize];
+    read_exact_at(file, &mut buf, offset)?;
+    Ok((u64::decode(&buf[..8]), T::decode(&buf[8..])))
+}
+
+fn write_elem<T: Record>(file: &File, offset: u64, elem: &T) -> io::Result<()> {
+    let mut buf = vec![0u8; T::SIZE];
+    elem.encode(&mut buf);
+    write_all_at(file, &buf, offset + 8)
+}
+
+fn write_record<T: Record>(file: &File, offset: u64, next: u64, elem: &T) -> io::Result<()> {
+    let mut buf = vec![0u8; record_size::<T>() as usize];
+    buf[..8].copy_from_slice(&next.to_le_bytes());
+    elem.encode(&mut buf[8..]);
+    write_all_at(file, &buf, offset)
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+}
+
+// `seek_read` and `seek_write` move the cursor as well, but they never depend on it
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Failed to fill whole buffer")),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            },
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &File, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_write(buf, offset) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "Failed to write whole buffer")),
+            Ok(n) => {
+                buf = &buf[n..];
+                offset += n as u64;
+            },
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+impl<T: Record + fmt::Debug> fmt::Debug for SinglyLinkedListDb<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut list = f.debug_list();
+        for elem in self.iter() {
+            match elem {
+                Ok(elem) => list.entry(&elem),
+                Err(_) => return Err(fmt::Error),
+            };
+        }
+        list.finish()
+    }
+}
+
+impl<'a, T: Record> Iterator for Iter<'a, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match read_record::<T>(self.file, self.cur) {
+            Ok((next, elem)) => {
+                self.cur = next;
+                self.remaining -= 1;
+                Some(Ok(elem))
+            },
+            Err(e) => {
+                self.remaining = 0; // stop after the first error
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
+
+impl<'a, T: Record> FusedIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::{HashSet, VecDeque};
+
+    fn collect<T: Record>(list: &SinglyLinkedListDb<T>) -> Vec<T> {
+        list.iter().collect::<io::Result<Vec<T>>>().unwrap()
+    }
+
+    impl<T: Record> SinglyLinkedListDb<T> {
+        // Every slot must be on the list, the spare or the free list, and on exactly one of them
+        fn check_invariants(&self) {
+            let header = self.header;
+            let mut seen = HashSet::new();
+            let mut cur = header.head;
+            let mut last = NIL;
+            for _ in 0..header.len {
+                assert!(seen.insert(cur));
+                last = cur;
+                cur = read_next(&self.file, cur).unwrap();
+            }
+            assert_eq!(last, header.tail);
+            if header.len == 0 {
+                assert_eq!(header.head, NIL);
+            }
+            if header.spare == NIL {
+                assert_eq!(header.free, NIL);
+            } else {
+                assert!(seen.insert(header.spare));
+            }
+            let mut cur = header.free;
+            while cur != NIL {
+                assert!(seen.insert(cur));
+                cur = read_next(&self.file, cur).unwrap();
+            }
+            assert_eq!(seen.len() as u64, header.slots);
+            for offset in seen {
+                assert_eq!((offset - HEADER_SIZE) % record_size::<T>(), 0);
+                assert!(offset < HEADER_SIZE + header.slots * record_size::<T>());
+            }
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let file = tempfile::tempfile().unwrap();
+        let mut list: SinglyLinkedListDb<u32> = SinglyLinkedListDb::from_file(file).unwrap();
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front().unwrap(), None);
+        assert_eq!(list.front().unwrap(), None);
+        list.push_back(2).unwrap();
+        list.push_front(1).unwrap();
+        list.push_back(3).unwrap();
+        assert_eq!(collect(&list), vec![1, 2, 3]);
+        assert_eq!((list.front().unwrap(), list.back().unwrap()), (Some(1), Some(3)));
+        assert_eq!(list.len(), 3);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(list.pop_front().unwrap(), Some(1));
+        assert_eq!(list.pop_front().unwrap(), Some(2));
+        assert_eq!(list.pop_front().unwrap(), Some(3));
+        assert_eq!(list.pop_front().unwrap(), None);
+        assert_eq!(list.back().unwrap(), None);
+        list.push_back(4).unwrap();
+        assert_eq!(collect(&list), vec![4]);
+    }
+
+    #[test]
+    fn survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.db");
+        {
+            let mut list: SinglyLinkedListDb<[u8; 4]> = SinglyLinkedListDb::open(&path).unwrap();
+            for elem in [*b"abcd", *b"efgh", *b"ijkl"].iter() {
+                list.push_back(*elem).unwrap();
+            }
+            list.pop_front().unwrap();
+        }
+        let mut list: SinglyLinkedListDb<[u8; 4]> = SinglyLinkedListDb::open(&path).unwrap();
+        assert_eq!(collect(&list), vec![*b"efgh", *b"ijkl"]);
+        list.push_back(*b"mnop").unwrap();
+        drop(list);
+        let list: SinglyLinkedListDb<[u8; 4]> = SinglyLinkedListDb::open(&path).unwrap();
+        assert_eq!(collect(&list), vec![*b"efgh", *b"ijkl", *b"mnop"]);
+        assert!(SinglyLinkedListDb::<u64>::open(&path).is_err());
+    }
+
+    #[test]
+    fn reuses_free_slots() {
+        let file = tempfile::tempfile().unwrap();
+        let mut list: SinglyLinkedListDb<u64> = SinglyLinkedListDb::from_file(file.try_clone().unwrap()).unwrap();
+        list.set_auto_sync(false);
+        for i in 0..100 {
+            list.push_back(i).unwrap();
+        }
+        let size = file.metadata().unwrap().len();
+        for _ in 0..60 {
+            list.pop_front().unwrap();
+        }
+        for i in 100..160 {
+            list.push_back(i).unwrap();
+        }
+        list.clear().unwrap();
+        assert!(list.is_empty());
+        for i in 0..100 {
+            list.push_front(i).unwrap();
+        }
+        list.sync().unwrap();
+        assert_eq!(file.metadata().unwrap().len(), size);
+        assert_eq!(collect(&list), (0..100).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn append() {
+        let mut list: SinglyLinkedListDb<i16> = SinglyLinkedListDb::from_file(tempfile::tempfile().unwrap()).unwrap();
+        let mut other: SinglyLinkedListDb<i16> = SinglyLinkedListDb::from_file(tempfile::tempfile().unwrap()).unwrap();
+        list.push_back(-1).unwrap();
+        for i in 0..5 {
+            other.push_back(i).unwrap();
+        }
+        list.append(&mut other).unwrap();
+        assert_eq!(collect(&list), vec![-1, 0, 1, 2, 3, 4]);
+        assert!(other.is_empty());
+        assert_eq!(collect(&other), vec![]);
+        list.append(&mut other).unwrap();
+        assert_eq!(collect(&list), vec![-1, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn append_reuses_free_slots() {
+        // (pushed, popped, appended): the free records cover the appended ones or fall short
+        for &(pushed, popped, appended) in [(10, 9, 3), (10, 7, 8), (6, 6, 6), (4, 1, 2), (0, 0, 3)].iter() {
+            let mut list: SinglyLinkedListDb<u32> = SinglyLinkedListDb::from_file(tempfile::tempfile().unwrap()).unwrap();
+            let mut other: SinglyLinkedListDb<u32> = SinglyLinkedListDb::from_file(tempfile::tempfile().unwrap()).unwrap();
+            for i in 0..pushed {
+                list.push_back(i).unwrap();
+            }
+            for _ in 0..popped {
+                list.pop_front().unwrap();
+            }
+            for i in 100..100 + appended {
+                other.push_back(i).unwrap();
+            }
+            list.append(&mut other).unwrap();
+            list.check_invariants();
+            other.check_invariants();
+            assert_eq!(list.header.slots, u64::from(pushed.max(pushed - popped + appended)));
+            let expected: Vec<u32> = (popped..pushed).chain(100..100 + appended).collect();
+            assert_eq!(collect(&list), expected);
+            assert!(other.is_empty());
+            list.push_back(7).unwrap();
+            list.check_invariants();
+            assert_eq!(list.back().unwrap(), Some(7));
+        }
+    }
+
+    #[test]
+    fn concurrent_iteration() {
+        let mut list: SinglyLinkedListDb<u64> = SinglyLinkedListDb::from_file(tempfile::tempfile().unwrap()).unwrap();
+        list.set_auto_sync(false);
+        for i in 0..2000 {
+            list.push_back(i).unwrap();
+        }
+        let list = &list;
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(move || {
+                    for _ in 0..5 {
+                        assert_eq!(collect(list), (0..2000).collect::<Vec<_>>());
+                    }
+                });
+            }
+        });
+    }
+
+    // Simulates a crash of every mutating operation after its record writes, right before
+    // the header write, by putting the old header back, and checks that the reopened file
+    // still holds the old, fully usable list
+    #[test]
+    fn crash_before_header_write() {
+        type Op = fn(&mut SinglyLinkedListDb<u32>, &mut VecDeque<u32>) -> io::Result<()>;
+        let ops: [Op; 5] = [
+            |list, model| { model.push_front(100); list.push_front(100) },
+            |list, model| { model.push_back(100); list.push_back(100) },
+            |list, model| { assert_eq!(list.pop_front()?, model.pop_front()); Ok(()) },
+            |list, model| { model.clear(); list.clear() },
+            |list, model| {
+                let mut other = SinglyLinkedListDb::from_file(tempfile::tempfile()?)?;
+                for i in 100..104 {
+                    other.push_back(i)?;
+                    model.push_back(i);
+                }
+                list.append(&mut other)
+            },
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crash.db");
+        // (pushed, popped) before the crash, giving lists with and without a spare and free list
+        for &(pushed, popped) in [(6, 3), (10, 9), (2, 1), (3, 0), (1, 0)].iter() {
+            for op in ops.iter() {
+                let _ = std::fs::remove_file(&path);
+                let mut model: VecDeque<u32> = (popped..pushed).collect();
+                let mut list = SinglyLinkedListDb::open(&path).unwrap();
+                for i in 0..pushed {
+                    list.push_back(i).unwrap();
+                }
+                for _ in 0..popped {
+                    list.pop_front().unwrap();
+                }
+                let old = std::fs::read(&path).unwrap();
+                op(&mut list, &mut model.clone()).unwrap();
+                drop(list);
+                let mut new = std::fs::read(&path).unwrap();
+                new[..HEADER_SIZE as usize].copy_from_slice(&old[..HEADER_SIZE as usize]);
+                std::fs::write(&path, new).unwrap();
+
+                let mut list = SinglyLinkedListDb::open(&path).unwrap();
+                list.check_invariants();
+                assert_eq!(collect(&list), Vec::from(model.clone()));
+                op(&mut list, &mut model).unwrap();
+                for i in 0..8 {
+                    list.push_back(i).unwrap();
+                    model.push_back(i);
+                    list.check_invariants();
+                }
+                while let Some(elem) = list.pop_front().unwrap() {
+                    assert_eq!(Some(elem), model.pop_front());
+                    list.check_invariants();
+                }
+                assert!(model.is_empty());
+            }
+        }
+    }
+}
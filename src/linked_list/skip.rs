@@ -0,0 +1,1010 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{BuildHasher, Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Bound, Index, RangeBounds};
+use core::ptr::NonNull;
+use std::collections::hash_map::RandomState;
+use crate::random::Xoshiro256StarStar;
+
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+// An ordered map on a tower of sorted singly linked lists. Level 0 holds every node and
+// each node also joins the next level with probability 1/2, so searches skip ahead on
+// the sparse upper levels and take O(log n) expected steps.
+pub struct SkipList<K, V> {
+    // `head[l]` is the first node on level `l`
+    head: [Link<K, V>; MAX_LEVEL],
+    tail: Link<K, V>,
+    // levels in use, the ones above are all empty
+    level: usize,
+    len: usize,
+    rng: Xoshiro256StarStar,
+    _marker: PhantomData<Box<Node<K, V>>>
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // one forward link for each level the node is on
+    next: Box<[Link<K, V>]>,
+}
+
+// An ordered set, a `SkipList` without values
+pub struct SkipSet<K> {
+    map: SkipList<K, ()>
+}
+
+pub struct Iter<'a, K: 'a, V: 'a> {
+    cur: Link<K, V>,
+    remaining: usize,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    cur: Link<K, V>,
+    remaining: usize,
+    _marker: PhantomData<&'a mut Node<K, V>>,
+}
+
+pub struct IntoIter<K, V> {
+    list: SkipList<K, V>
+}
+
+// Yields the nodes from `cur` up to, but not including, `end`
+pub struct Range<'a, K: 'a, V: 'a> {
+    cur: Link<K, V>,
+    end: Link<K, V>,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+pub struct Keys<'a, K: 'a, V: 'a> {
+    inner: Range<'a, K, V>
+}
+
+pub struct Values<'a, K: 'a, V: 'a> {
+    inner: Range<'a, K, V>
+}
+
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>
+}
+
+impl<K, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    // A list whose node levels are drawn from a PRNG seeded with `seed`, for reproducible shapes
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            head: [None; MAX_LEVEL],
+            tail: None,
+            level: 0,
+            len: 0,
+            rng: Xoshiro256StarStar::new(seed),
+            _marker: PhantomData
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { cur: self.head[0], remaining: self.len, _marker: PhantomData }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { cur: self.head[0], remaining: self.len, _marker: PhantomData }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: Range { cur: self.head[0], end: None, _marker: PhantomData } }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: Range { cur: self.head[0], end: None, _marker: PhantomData } }
+    }
+
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys { inner: self.into_iter() }
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.head[0].map(|node_ptr| {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            (&node.key, &node.value)
+        })
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.tail.map(|node_ptr| {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            (&node.key, &node.value)
+        })
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        // the head precedes the first node on every level
+        self.head[0].map(|node_ptr| self.unlink(&[None; MAX_LEVEL], node_ptr))
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    fn random_height(&mut self) -> usize {
+        (self.rng.next_u64().trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    // The link after `pred` on `level`, `None` standing for the head
+    fn next_at(&self, pred: Link<K, V>, level: usize) -> Link<K, V> {
+        match pred {
+            Some(pred_ptr) => unsafe { (*pred_ptr.as_ptr()).next[level] },
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, pred: Link<K, V>, level: usize, next: Link<K, V>) {
+        match pred {
+            Some(pred_ptr) => unsafe { (*pred_ptr.as_ptr()).next[level] = next },
+            None => self.head[level] = next,
+        }
+    }
+
+    // For every level, the last node whose key satisfies `before`, or `None` for the head.
+    // `before` must hold for a prefix of the keys.
+    fn predecessors<F>(&self, before: F) -> [Link<K, V>; MAX_LEVEL]
+    where F: Fn(&K) -> bool
+    {
+        let mut preds = [None; MAX_LEVEL];
+        let mut pred = None;
+        for level in (0..self.level).rev() {
+            while let Some(next_ptr) = self.next_at(pred, level) {
+                if !before(unsafe { &(*next_ptr.as_ptr()).key }) {
+                    break;
+                }
+                pred = Some(next_ptr);
+            }
+            preds[level] = pred;
+        }
+        preds
+    }
+
+    // The first node whose key does not satisfy `before`
+    fn first_after<F>(&self, before: F) -> Link<K, V>
+    where F: Fn(&K) -> bool
+    {
+        self.next_at(self.predecessors(before)[0], 0)
+    }
+
+    fn unlink(&mut self, preds: &[Link<K, V>; MAX_LEVEL], node_ptr: NonNull<Node<K, V>>) -> (K, V) {
+        let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+        for (level, &next) in node.next.iter().enumerate() {
+            self.set_next(preds[level], level, next);
+        }
+        if node.next[0].is_none() {
+            self.tail = preds[0];
+        }
+        self.len -= 1;
+        self.trim_levels();
+        (node.key, node.value)
+    }
+
+    fn trim_levels(&mut self) {
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+    }
+}
+
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        let mut cur = self.head[0];
+        while let Some(node_ptr) = cur {
+            let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+            cur = node.next[0];
+        }
+    }
+}
+
+// O(log n) operations
+impl<K: Ord, V> SkipList<K, V> {
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        let node_ptr = self.first_after(|k| k.borrow() < key)?;
+        if unsafe { &*node_ptr.as_ptr() }.key.borrow() == key {
+            Some(node_ptr)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.find(key).map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.find(key).map(|node_ptr| {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            (&node.key, &node.value)
+        })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.find(key).map(|node_ptr| &mut unsafe { &mut *node_ptr.as_ptr() }.value)
+    }
+
+    // Returns the old value if `key` was present, the key itself is not updated
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.predecessors(|k| k < &key);
+        if let Some(node_ptr) = self.next_at(preds[0], 0) {
+            let node = unsafe { &mut *node_ptr.as_ptr() };
+            if node.key == key {
+                return Some(core::mem::replace(&mut node.value, value));
+            }
+        }
+        let height = self.random_height();
+        // `preds` holds the head for every level that is not in use yet
+        self.level = self.level.max(height);
+        let node = Box::new(Node { key, value, next: vec![None; height].into_boxed_slice() });
+        let node_ptr = NonNull::from(Box::leak(node));
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            unsafe { (*node_ptr.as_ptr()).next[level] = self.next_at(pred, level) };
+            self.set_next(pred, level, Some(node_ptr));
+        }
+        if unsafe { node_ptr.as_ref() }.next[0].is_none() {
+            self.tail = Some(node_ptr);
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        let preds = self.predecessors(|k| k.borrow() < key);
+        let node_ptr = self.next_at(preds[0], 0)?;
+        if unsafe { &*node_ptr.as_ptr() }.key.borrow() != key {
+            return None;
+        }
+        Some(self.unlink(&preds, node_ptr))
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let tail_ptr = self.tail?;
+        let tail_key = unsafe { &(*tail_ptr.as_ptr()).key };
+        let preds = self.predecessors(|k| k < tail_key);
+        Some(self.unlink(&preds, tail_ptr))
+    }
+
+    // Entries whose keys lie in `range`, in order. Empty when the range is reversed.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q>
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.first_after(|k| k.borrow() < start),
+            Bound::Excluded(start) => self.first_after(|k| k.borrow() <= start),
+            Bound::Unbounded => self.head[0],
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.first_after(|k| k.borrow() <= end),
+            Bound::Excluded(end) => self.first_after(|k| k.borrow() < end),
+            Bound::Unbounded => None,
+        };
+        let reversed = match (start, end) {
+            (Some(start_ptr), Some(end_ptr)) => unsafe { (*start_ptr.as_ptr()).key > (*end_ptr.as_ptr()).key },
+            _ => false,
+        };
+        let cur = if reversed { None } else { start };
+        Range { cur, end, _marker: PhantomData }
+    }
+}
+
+// O(n) operations
+impl<K: Ord, V> SkipList<K, V> {
+    // Moves every entry with a key not less than `key` into the returned list. Relinking
+    // takes O(log n), counting the moved entries takes time linear in their number.
+    pub fn split_off<Q>(&mut self, key: &Q) -> SkipList<K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        let mut second_part = SkipList::with_seed(self.rng.next_u64());
+        let preds = self.predecessors(|k| k.borrow() < key);
+        if self.next_at(preds[0], 0).is_none() {
+            return second_part;
+        }
+        for (level, &pred) in preds.iter().enumerate().take(self.level) {
+            second_part.head[level] = self.next_at(pred, level);
+            self.set_next(pred, level, None);
+        }
+        second_part.tail = self.tail;
+        second_part.level = self.level;
+        second_part.len = second_part.keys().count();
+        self.tail = preds[0];
+        self.len -= second_part.len;
+        self.trim_levels();
+        second_part.trim_levels();
+        second_part
+    }
+
+    // Moves all entries of `other` into this list, replacing values of equal keys. When every
+    // key of `other` is greater than those of this list the towers are spliced in O(log n).
+    pub fn append(&mut self, other: &mut Self) {
+        let disjoint = match (self.last(), other.first()) {
+            (Some((last, _)), Some((first, _))) => last < first,
+            _ => true,
+        };
+        if !disjoint {
+            while let Some((key, value)) = other.pop_first() {
+                self.insert(key, value);
+            }
+            return;
+        }
+        let preds = self.predecessors(|_| true);
+        for (level, &pred) in preds.iter().enumerate().take(other.level) {
+            self.set_next(pred, level, other.head[level].take());
+        }
+        if other.tail.is_some() {
+            self.tail = other.tail.take();
+        }
+        self.level = self.level.max(other.level);
+        self.len += other.len;
+        other.level = 0;
+        other.len = 0;
+    }
+}
+
+impl<K, V> IntoIterator for SkipList<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipList<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash, V: Hash> Hash for SkipList<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SkipList<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SkipList<K, V> {}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for SkipList<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for SkipList<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<K: Ord + Borrow<Q>, Q: Ord + ?Sized, V> Index<&Q> for SkipList<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("Cannot access a nonexistent key")
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: 'a + Ord + Copy, V: 'a + Copy> Extend<(&'a K, &'a V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipList<K, V> {
+    fn clone(&self) -> Self {
+        let mut list = Self::with_seed(self.rng.clone().next_u64());
+        list.extend(self.iter().map(|(key, value)| (key.clone(), value.clone())));
+        list
+    }
+}
+
+impl<K, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<K: Send, V: Send> Send for SkipList<K, V> {}
+
+unsafe impl<K: Sync, V: Sync> Sync for SkipList<K, V> {}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.cur.map(|node_ptr| {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            self.cur = node.next[0];
+            self.remaining -= 1;
+            (&node.key, &node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter { cur: self.cur, remaining: self.remaining, _marker: PhantomData }
+    }
+}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a + fmt::Debug> fmt::Debug for Iter<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").finish()
+    }
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Iter<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        self.cur.map(|node_ptr| {
+            let node = unsafe { &mut *node_ptr.as_ptr() };
+            self.cur = node.next[0];
+            self.remaining -= 1;
+            (&node.key, &mut node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a + fmt::Debug> fmt::Debug for IterMut<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IterMut").finish()
+    }
+}
+
+unsafe impl<'a, K: Sync, V: Send> Send for IterMut<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Sync for IterMut<'a, K, V> {}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.list.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IntoIter<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.list).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.cur == self.end {
+            return None;
+        }
+        self.cur.map(|node_ptr| {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            self.cur = node.next[0];
+            (&node.key, &node.value)
+        })
+    }
+}
+
+impl<'a, K, V> FusedIterator for Range<'a, K, V> {}
+
+impl<'a, K, V> Clone for Range<'a, K, V> {
+    fn clone(&self) -> Self {
+        Range { cur: self.cur, end: self.end, _marker: PhantomData }
+    }
+}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a + fmt::Debug> fmt::Debug for Range<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Range").finish()
+    }
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Range<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Sync for Range<'a, K, V> {}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> Clone for Keys<'a, K, V> {
+    fn clone(&self) -> Self {
+        Keys { inner: self.inner.clone() }
+    }
+}
+
+impl<'a, K: 'a + fmt::Debug, V: 'a> fmt::Debug for Keys<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Keys").finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|(_, value)| value)
+    }
+}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+impl<'a, K, V> Clone for Values<'a, K, V> {
+    fn clone(&self) -> Self {
+        Values { inner: self.inner.clone() }
+    }
+}
+
+impl<'a, K: 'a, V: 'a + fmt::Debug> fmt::Debug for Values<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Values").finish()
+    }
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+impl<K, V> FusedIterator for IntoKeys<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IntoKeys<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IntoKeys").field(&self.inner).finish()
+    }
+}
+
+impl<K> SkipSet<K> {
+    pub fn new() -> Self {
+        Self { map: SkipList::new() }
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { map: SkipList::with_seed(seed) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn iter(&self) -> Keys<'_, K, ()> {
+        self.map.keys()
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.map.first().map(|(key, _)| key)
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.map.last().map(|(key, _)| key)
+    }
+
+    pub fn pop_first(&mut self) -> Option<K> {
+        self.map.pop_first().map(|(key, _)| key)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<K: Ord> SkipSet<K> {
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.map.contains_key(value)
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&K>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.map.get_key_value(value).map(|(key, _)| key)
+    }
+
+    // Whether `value` was newly inserted
+    pub fn insert(&mut self, value: K) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.map.remove(value).is_some()
+    }
+
+    pub fn take<Q>(&mut self, value: &Q) -> Option<K>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        self.map.remove_entry(value).map(|(key, _)| key)
+    }
+
+    pub fn pop_last(&mut self) -> Option<K> {
+        self.map.pop_last().map(|(key, _)| key)
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Keys<'_, K, ()>
+    where K: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q>
+    {
+        Keys { inner: self.map.range(range) }
+    }
+
+    pub fn split_off<Q>(&mut self, value: &Q) -> SkipSet<K>
+    where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        SkipSet { map: self.map.split_off(value) }
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.map.append(&mut other.map)
+    }
+}
+
+impl<K> IntoIterator for SkipSet<K> {
+    type Item = K;
+    type IntoIter = IntoKeys<K, ()>;
+
+    fn into_iter(self) -> IntoKeys<K, ()> {
+        self.map.into_keys()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a SkipSet<K> {
+    type Item = &'a K;
+    type IntoIter = Keys<'a, K, ()>;
+
+    fn into_iter(self) -> Keys<'a, K, ()> {
+        self.iter()
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for SkipSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<K: Hash> Hash for SkipSet<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<K: PartialEq> PartialEq for SkipSet<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K: Eq> Eq for SkipSet<K> {}
+
+impl<K: PartialOrd> PartialOrd for SkipSet<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K: Ord> Ord for SkipSet<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<K: Ord> Extend<K> for SkipSet<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, K: 'a + Ord + Copy> Extend<&'a K> for SkipSet<K> {
+    fn extend<I: IntoIterator<Item = &'a K>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<K: Ord> FromIterator<K> for SkipSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K: Ord + Clone> Clone for SkipSet<K> {
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone() }
+    }
+}
+
+impl<K> Default for SkipSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+impl<K: Ord, V> SkipList<K, V> {
+    fn check_invariants(&self) {
+        assert!(self.head[self.level..].iter().all(Option::is_none));
+        assert_eq!(self.len == 0, self.level == 0);
+        let mut below: Vec<NonNull<Node<K, V>>> = Vec::new();
+        for level in 0..self.level {
+            let mut on_level = Vec::new();
+            let mut cur = self.head[level];
+            while let Some(node_ptr) = cur {
+                let node = unsafe { &*node_ptr.as_ptr() };
+                assert!(node.next.len() > level);
+                on_level.push(node_ptr);
+                cur = node.next[level];
+            }
+            let keys: Vec<&K> = on_level.iter().map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.key).collect();
+            assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+            if level == 0 {
+                assert_eq!(on_level.len(), self.len);
+                assert_eq!(on_level.last().cloned(), self.tail);
+            } else {
+                // every node on a level is also on all the levels below it
+                let mut rest = below.iter();
+                assert!(on_level.iter().all(|node_ptr| rest.any(|below_ptr| below_ptr == node_ptr)));
+            }
+            below = on_level;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn map_operations() {
+        let mut map = SkipList::with_seed(1);
+        assert_eq!(map.insert("b", 2), None);
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("c", 3), None);
+        assert_eq!(map.insert("b", 20), Some(2));
+        assert_eq!(format!("{:?}", map), r#"{"a": 1, "b": 20, "c": 3}"#);
+        assert_eq!((map.first(), map.last()), (Some((&"a", &1)), Some((&"c", &3))));
+        assert_eq!(map["b"], 20);
+        *map.get_mut("c").unwrap() += 10;
+        for (_, value) in map.iter_mut() {
+            *value += 1;
+        }
+        assert_eq!(map.values().cloned().collect::<Vec<_>>(), vec![2, 21, 14]);
+        assert_eq!(map.remove("b"), Some(21));
+        assert_eq!(map.remove("b"), None);
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.pop_last(), Some(("c", 14)));
+        assert_eq!(map.pop_first(), Some(("a", 2)));
+        assert_eq!(map.pop_first(), None);
+        map.check_invariants();
+    }
+
+    #[test]
+    fn range_iteration() {
+        let map: SkipList<u32, u32> = (0..20).map(|i| (i * 2, i)).collect();
+        let keys = |range: Range<'_, u32, u32>| range.map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(3..9)), vec![4, 6, 8]);
+        assert_eq!(keys(map.range(4..=8)), vec![4, 6, 8]);
+        assert_eq!(keys(map.range((Bound::Excluded(4), Bound::Included(10)))), vec![6, 8, 10]);
+        assert_eq!(keys(map.range(35..)), vec![36, 38]);
+        assert_eq!(keys(map.range(..3)), vec![0, 2]);
+        assert_eq!(keys(map.range((Bound::Included(9), Bound::Excluded(5)))), vec![]);
+        assert_eq!(keys(map.range(5..5)), vec![]);
+        assert_eq!(keys(map.range(100..)), vec![]);
+        assert_eq!(map.range(..).count(), 20);
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut map: SkipList<u32, ()> = (0..100).map(|i| (i, ())).collect();
+        let mut second = map.split_off(&60);
+        map.check_invariants();
+        second.check_invariants();
+        assert_eq!((map.len(), second.len()), (60, 40));
+        assert_eq!(map.last(), Some((&59, &())));
+        assert_eq!(second.first(), Some((&60, &())));
+        assert!(map.split_off(&1000).is_empty());
+        map.append(&mut second);
+        map.check_invariants();
+        assert!(second.is_empty());
+        second.check_invariants();
+        assert!(map.keys().cloned().eq(0..100));
+        let mut evens: SkipList<u32, ()> = (0..50).map(|i| (i * 2, ())).collect();
+        let all = map.split_off(&0);
+        assert!(map.is_empty());
+        evens.append(&mut map.clone());
+        let mut all_clone = all.clone();
+        evens.append(&mut all_clone);
+        evens.check_invariants();
+        assert_eq!(evens, all);
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = Xoshiro256StarStar::new(47);
+        let mut map = SkipList::with_seed(47);
+        let mut model = BTreeMap::new();
+        for step in 0..20000u32 {
+            let key = rng.below(500) as u32;
+            match rng.below(8) {
+                0..=2 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+                3 | 4 => assert_eq!(map.remove(&key), model.remove(&key)),
+                5 => assert_eq!(map.get(&key), model.get(&key)),
+                6 => {
+                    let hi = key + rng.below(50) as u32;
+                    assert!(map.range(key..hi).eq(model.range(key..hi)));
+                },
+                _ => match rng.below(3) {
+                    0 => assert_eq!(map.pop_first(), model.pop_first()),
+                    1 => assert_eq!(map.pop_last(), model.pop_last()),
+                    _ => {
+                        let mut second = map.split_off(&key);
+                        let mut model_second = model.split_off(&key);
+                        assert!(second.iter().eq(model_second.iter()));
+                        second.insert(key / 2, step);
+                        model_second.insert(key / 2, step);
+                        map.append(&mut second);
+                        model.append(&mut model_second);
+                    }
+                }
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        map.check_invariants();
+        assert!(map.into_iter().eq(model));
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut set: SkipSet<u32> = vec![5, 1, 3, 1, 9].into_iter().collect();
+        assert_eq!(format!("{:?}", set), "{1, 3, 5, 9}");
+        assert!(!set.insert(3));
+        assert!(set.insert(4));
+        assert!(set.contains(&4) && !set.contains(&2));
+        assert_eq!(set.range(2..6).cloned().collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert!(set.remove(&4));
+        assert_eq!(set.take(&9), Some(9));
+        let mut high = set.split_off(&3);
+        assert_eq!((set.first(), set.last()), (Some(&1), Some(&1)));
+        assert_eq!(high.pop_last(), Some(5));
+        set.append(&mut high);
+        assert_eq!(set.clone().into_iter().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(set.pop_first(), Some(1));
+        assert_eq!(set.get(&3), Some(&3));
+    }
+}
@@ -8,7 +8,7 @@ mod $module_name {
     use core::iter::FromIterator;
     #[test]
     fn new_list() {
-        let mut list: $ll_name<usize> = $ll_name::new();
+        let mut list: $ll_name<usize> = <$ll_name<_>>::new();
         assert!(list.is_empty());
         assert!(!list.contains(&233usize));
         assert_eq!(list.len(), 0);
@@ -21,17 +21,17 @@ mod $module_name {
         assert_eq!(list.pop_front(), None);
         assert_eq!(list.pop_back(), None);
         let second = list.split_off(0);
-        assert_eq!(list, $ll_name::new());
-        assert_eq!(second, $ll_name::new());
+        assert_eq!(list, <$ll_name<_>>::new());
+        assert_eq!(second, <$ll_name<_>>::new());
     }
 
     #[test]
     fn small_item() {
-        let mut list = $ll_name::from_iter(vec![1, 2, 3]);
+        let mut list = <$ll_name<_>>::from_iter(vec![1, 2, 3]);
         assert!(!list.is_empty());
         assert_eq!(list.len(), 3);
-        assert_eq!(list, $ll_name::from_iter(vec![1, 2, 3]));
-        assert_ne!(list, $ll_name::from_iter(vec![10, 2, 3]));
+        assert_eq!(list, <$ll_name<_>>::from_iter(vec![1, 2, 3]));
+        assert_ne!(list, <$ll_name<_>>::from_iter(vec![10, 2, 3]));
         list.push_back(4);
         assert_eq!(format!("{:?}", list), "[1, 2, 3, 4]");
         assert_eq!(list.pop_front(), Some(1));
@@ -43,7 +43,7 @@ mod $module_name {
         assert_eq!(list.pop_front(), Some(3)); 
         assert_eq!(list.pop_front(), None); 
         assert_eq!(list.pop_back(), None); 
-        let mut list = $ll_name::from_iter(vec![6, 7, 8, 9, 10]);
+        let mut list = <$ll_name<_>>::from_iter(vec![6, 7, 8, 9, 10]);
         let second = list.split_off(3);
         assert_eq!(format!("{:?} {:?}", list, second), "[6, 7, 8] [9, 10]");
         assert!(((list < second)&&(second > list))||
@@ -53,15 +53,15 @@ mod $module_name {
     #[test] 
     #[should_panic(expected = "Cannot split off a nonexistent index")]
     fn invalid_split_off() {
-        let mut list = $ll_name::from_iter(&[10, 11, 12]);
+        let mut list = <$ll_name<_>>::from_iter(&[10, 11, 12]);
         assert_eq!(list.split_off(0).len(), 3); // okay! []  [10, 11, 12]
-        let mut list = $ll_name::from_iter(&[10, 11, 12]);
+        let mut list = <$ll_name<_>>::from_iter(&[10, 11, 12]);
         assert_eq!(list.split_off(1).len(), 2); // okay! [10]    [11, 12]
-        let mut list = $ll_name::from_iter(&[10, 11, 12]);
+        let mut list = <$ll_name<_>>::from_iter(&[10, 11, 12]);
         assert_eq!(list.split_off(2).len(), 1); // okay! [10, 11]    [12]
-        let mut list = $ll_name::from_iter(&[10, 11, 12]);
+        let mut list = <$ll_name<_>>::from_iter(&[10, 11, 12]);
         assert_eq!(list.split_off(3).len(), 0); // okay! [10, 11, 12]  []
-        let mut list = $ll_name::from_iter(&[10, 11, 12]);
+        let mut list = <$ll_name<_>>::from_iter(&[10, 11, 12]);
         list.split_off(4); // panic!
     }
 
@@ -72,7 +72,7 @@ mod $module_name {
         for i in 0..=qty {
             vec.push(i * 5 + 4);
         }
-        let mut list = $ll_name::new();
+        let mut list = <$ll_name<_>>::new();
         list.extend(vec);
         assert!(!list.is_empty());
         assert_eq!(list.len(), qty+1);
@@ -84,6 +84,323 @@ mod $module_name {
             assert_eq!(list.pop_front(), Some(i * 5 + 4));
         }
     }
+
+    #[test]
+    fn model_conformance() {
+        super::testing::model::check_conformance::<$ll_name<u32>>(20, 500);
+    }
 }
     };
 }
+
+// Model-based conformance testing: random operation sequences are applied both to a list
+// and to a `VecDeque` reference model, comparing the two after every step. A failing
+// sequence is shrunk to a minimal reproducer before it is reported.
+#[cfg(test)]
+pub(crate) mod model {
+    use super::super::List;
+    use crate::random::Xoshiro256StarStar;
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Op {
+        PushFront(u32),
+        PushBack(u32),
+        PopFront,
+        PopBack,
+        // split at `n % (len + 1)` and drop the second part
+        SplitOff(usize),
+        // split at `n % (len + 1)` and append the second part back
+        SplitAppend(usize),
+        Append(Vec<u32>),
+        Extend(Vec<u32>),
+        Clear,
+        EditFront(u32),
+        EditBack(u32),
+        IterMutAdd(u32),
+    }
+
+    pub fn random_ops(rng: &mut Xoshiro256StarStar, steps: usize) -> Vec<Op> {
+        let small_vec = |rng: &mut Xoshiro256StarStar| -> Vec<u32> {
+            (0..rng.gen_index(5)).map(|_| rng.below(100) as u32).collect()
+        };
+        (0..steps).map(|_| match rng.gen_index(20) {
+            0..=3 => Op::PushFront(rng.below(100) as u32),
+            4..=7 => Op::PushBack(rng.below(100) as u32),
+            8..=9 => Op::PopFront,
+            10..=11 => Op::PopBack,
+            12 => Op::SplitOff(rng.gen_index(usize::MAX)),
+            13 => Op::SplitAppend(rng.gen_index(usize::MAX)),
+            14 => Op::Append(small_vec(rng)),
+            15 => Op::Extend(small_vec(rng)),
+            16 => Op::EditFront(rng.below(100) as u32),
+            17 => Op::EditBack(rng.below(100) as u32),
+            18 => Op::IterMutAdd(rng.below(100) as u32),
+            _ => if rng.gen_index(4) == 0 { Op::Clear } else { Op::PopFront },
+        }).collect()
+    }
+
+    fn compare<L: List<u32>>(list: &L, model: &VecDeque<u32>) -> Result<(), String> {
+        if list.len() != model.len() || list.is_empty() != model.is_empty() {
+            return Err(format!("len is {}, expected {}", list.len(), model.len()));
+        }
+        if list.front() != model.front() || list.back() != model.back() {
+            return Err(format!("ends are {:?} and {:?}, expected {:?} and {:?}", 
+                list.front(), list.back(), model.front(), model.back()));
+        }
+        if !list.iter().eq(model.iter()) {
+            return Err(format!("elements are {:?}, expected {:?}", list.iter().collect::<Vec<_>>(), model));
+        }
+        Ok(())
+    }
+
+    fn step<L: List<u32>>(list: &mut L, model: &mut VecDeque<u32>, op: &Op) -> Result<(), String> {
+        match op {
+            Op::PushFront(x) => {
+                list.push_front(*x);
+                model.push_front(*x);
+            },
+            Op::PushBack(x) => {
+                list.push_back(*x);
+                model.push_back(*x);
+            },
+            Op::PopFront => {
+                let (got, expected) = (list.pop_front(), model.pop_front());
+                if got != expected {
+                    return Err(format!("pop_front gave {:?}, expected {:?}", got, expected));
+                }
+            },
+            Op::PopBack => {
+                let (got, expected) = (list.pop_back(), model.pop_back());
+                if got != expected {
+                    return Err(format!("pop_back gave {:?}, expected {:?}", got, expected));
+                }
+            },
+            Op::SplitOff(n) | Op::SplitAppend(n) => {
+                let at = n % (model.len() + 1);
+                let mut second = list.split_off(at);
+                let second_model = model.split_off(at);
+                compare(&second, &second_model).map_err(|e| format!("second part of split_off: {}", e))?;
+                if let Op::SplitAppend(_) = op {
+                    list.append(&mut second);
+                    model.extend(second_model);
+                    compare(&second, &VecDeque::new()).map_err(|e| format!("appended list: {}", e))?;
+                }
+            },
+            Op::Append(elems) => {
+                let mut other: L = elems.iter().cloned().collect();
+                list.append(&mut other);
+                model.extend(elems.iter().cloned());
+                compare(&other, &VecDeque::new()).map_err(|e| format!("appended list: {}", e))?;
+            },
+            Op::Extend(elems) => {
+                list.extend(elems.iter().cloned());
+                model.extend(elems.iter().cloned());
+            },
+            Op::Clear => {
+                list.clear();
+                model.clear();
+            },
+            Op::EditFront(x) => {
+                if let (Some(got), Some(expected)) = (list.front_mut(), model.front_mut()) {
+                    *got = *x;
+                    *expected = *x;
+                }
+            },
+            Op::EditBack(x) => {
+                if let (Some(got), Some(expected)) = (list.back_mut(), model.back_mut()) {
+                    *got = *x;
+                    *expected = *x;
+                }
+            },
+            Op::IterMutAdd(x) => {
+                for elem in list.iter_mut() {
+                    *elem = elem.wrapping_add(*x);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(*x);
+                }
+            },
+        }
+        compare(list, model)
+    }
+
+    // Index and description of the first step where the list diverges from the model
+    pub fn run<L: List<u32>>(ops: &[Op]) -> Result<(), (usize, String)> {
+        let mut list = L::default();
+        let mut model = VecDeque::new();
+        for (i, op) in ops.iter().enumerate() {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| step(&mut list, &mut model, op)));
+            match result {
+                Ok(Ok(())) => {},
+                Ok(Err(e)) => return Err((i, e)),
+                Err(payload) => {
+                    let msg = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    // the list may be left in an inconsistent state, don't drop it
+                    std::mem::forget(list);
+                    return Err((i, format!("panicked: {}", msg)));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn simpler(op: &Op) -> Vec<Op> {
+        match op {
+            Op::Append(elems) | Op::Extend(elems) if !elems.is_empty() => {
+                let shorter = elems[..elems.len() - 1].to_vec();
+                vec![if let Op::Append(_) = op { Op::Append(shorter) } else { Op::Extend(shorter) }]
+            },
+            Op::PushFront(x) | Op::PushBack(x) | Op::EditFront(x) | Op::EditBack(x) | Op::IterMutAdd(x) if *x != 0 => {
+                let mut zeroed = op.clone();
+                match &mut zeroed {
+                    Op::PushFront(x) | Op::PushBack(x) | Op::EditFront(x) | Op::EditBack(x) | Op::IterMutAdd(x) => *x = 0,
+                    _ => unreachable!(),
+                }
+                vec![zeroed]
+            },
+            Op::SplitOff(n) | Op::SplitAppend(n) if *n > 0 => {
+                let mut halved = op.clone();
+                match &mut halved {
+                    Op::SplitOff(n) | Op::SplitAppend(n) => *n /= 2,
+                    _ => unreachable!(),
+                }
+                vec![halved]
+            },
+            _ => vec![],
+        }
+    }
+
+    // Removes chunks of operations and simplifies the remaining ones while the sequence still fails
+    pub fn shrink<L: List<u32>>(mut ops: Vec<Op>) -> Vec<Op> {
+        if let Err((i, _)) = run::<L>(&ops) {
+            ops.truncate(i + 1);
+        }
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start + chunk <= ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..start + chunk);
+                if run::<L>(&candidate).is_err() {
+                    ops = candidate;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for i in 0..ops.len() {
+                for replacement in simpler(&ops[i]) {
+                    let mut candidate = ops.clone();
+                    candidate[i] = replacement;
+                    if run::<L>(&candidate).is_err() {
+                        ops = candidate;
+                        progress = true;
+                    }
+                }
+            }
+        }
+        ops
+    }
+
+    struct Reproducer<'a>(&'a [Op]);
+
+    impl<'a> fmt::Display for Reproducer<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for op in self.0 {
+                writeln!(f, "    {:?},", op)?;
+            }
+            Ok(())
+        }
+    }
+
+    // The seed can be overridden with the `LJ_TEST_SEED` environment variable
+    pub fn check_conformance<L: List<u32>>(cases: usize, steps: usize) {
+        let seed = std::env::var("LJ_TEST_SEED").ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0x5eed_1157);
+        let mut rng = Xoshiro256StarStar::new(seed);
+        for case in 0..cases {
+            let ops = random_ops(&mut rng, steps);
+            if run::<L>(&ops).is_err() {
+                let minimal = shrink::<L>(ops);
+                let (i, e) = run::<L>(&minimal).unwrap_err();
+                panic!("list diverged from the model at step {} ({}) in case {} of seed {}, minimal reproducer:\n{}", 
+                    i, e, case, seed, Reproducer(&minimal));
+            }
+        }
+    }
+
+    // A list whose `pop_back` misbehaves once it holds three elements
+    #[derive(Default)]
+    struct BuggyList(VecDeque<u32>);
+
+    impl List<u32> for BuggyList {
+        type Iter<'a> = std::collections::vec_deque::Iter<'a, u32>;
+        type IterMut<'a> = std::collections::vec_deque::IterMut<'a, u32>;
+
+        fn len(&self) -> usize { self.0.len() }
+        fn iter(&self) -> Self::Iter<'_> { self.0.iter() }
+        fn iter_mut(&mut self) -> Self::IterMut<'_> { self.0.iter_mut() }
+        fn clear(&mut self) { self.0.clear() }
+        fn front(&self) -> Option<&u32> { self.0.front() }
+        fn front_mut(&mut self) -> Option<&mut u32> { self.0.front_mut() }
+        fn back(&self) -> Option<&u32> { self.0.back() }
+        fn back_mut(&mut self) -> Option<&mut u32> { self.0.back_mut() }
+        fn push_front(&mut self, elem: u32) { self.0.push_front(elem) }
+        fn push_back(&mut self, elem: u32) { self.0.push_back(elem) }
+        fn pop_front(&mut self) -> Option<u32> { self.0.pop_front() }
+        fn pop_back(&mut self) -> Option<u32> {
+            if self.0.len() >= 3 { self.0.pop_front() } else { self.0.pop_back() }
+        }
+        fn split_off(&mut self, at: usize) -> Self { BuggyList(self.0.split_off(at)) }
+        fn append(&mut self, other: &mut Self) { self.0.append(&mut other.0) }
+    }
+
+    impl Extend<u32> for BuggyList {
+        fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+            self.0.extend(iter)
+        }
+    }
+
+    impl core::iter::FromIterator<u32> for BuggyList {
+        fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+            BuggyList(iter.into_iter().collect())
+        }
+    }
+
+    impl IntoIterator for BuggyList {
+        type Item = u32;
+        type IntoIter = std::collections::vec_deque::IntoIter<u32>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+    }
+
+    #[test]
+    fn shrinks_to_minimal_reproducer() {
+        let mut rng = Xoshiro256StarStar::new(1);
+        let ops = random_ops(&mut rng, 500);
+        assert!(run::<VecDeque<u32>>(&ops).is_ok());
+        assert!(run::<BuggyList>(&ops).is_err());
+        let minimal = shrink::<BuggyList>(ops);
+        assert!(minimal.len() <= 4, "{:?}", minimal);
+        assert_eq!(minimal.last(), Some(&Op::PopBack));
+        assert!(run::<BuggyList>(&minimal).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal reproducer")]
+    fn reports_divergence() {
+        check_conformance::<BuggyList>(1, 200);
+    }
+}
@@ -0,0 +1,148 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use super::hazard::{self, HazardPointer};
+
+// Treiber's lock-free stack. Popped nodes are reclaimed with hazard pointers,
+// which also rules out the ABA problem on `head`.
+pub struct ConcurrentStack<T> {
+    head: AtomicPtr<Node<T>>,
+    _marker: PhantomData<Box<Node<T>>>
+}
+
+struct Node<T> {
+    // moved out by the thread whose pop unlinks the node
+    elem: ManuallyDrop<T>,
+    // never changes once the node is published
+    next: *mut Node<T>
+}
+
+impl<T> ConcurrentStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            _marker: PhantomData
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node { elem: ManuallyDrop::new(elem), next: ptr::null_mut() }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*node).next = head };
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = HazardPointer::new();
+        loop {
+            let head = hazard.protect(&self.head);
+            if head.is_null() {
+                return None;
+            }
+            let next = unsafe { (*head).next };
+            if self.head.compare_exchange(head, next, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                hazard.clear();
+                let elem = unsafe { ptr::read(&(*head).elem) };
+                unsafe { hazard::retire(head) };
+                return Some(ManuallyDrop::into_inner(elem));
+            }
+        }
+    }
+}
+
+impl<T> Drop for ConcurrentStack<T> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = node.next;
+            unsafe { ManuallyDrop::drop(&mut node.elem) };
+        }
+    }
+}
+
+impl<T> Default for ConcurrentStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ConcurrentStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConcurrentStack").field("is_empty", &self.is_empty()).finish()
+    }
+}
+
+unsafe impl<T: Send> Send for ConcurrentStack<T> {}
+
+unsafe impl<T: Send> Sync for ConcurrentStack<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn lifo_order() {
+        let stack = ConcurrentStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+        for i in 0..5 {
+            stack.push(i);
+        }
+        assert_eq!((0..5).map(|_| stack.pop().unwrap()).collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let counter = Arc::new(());
+        let stack = ConcurrentStack::new();
+        for _ in 0..10 {
+            stack.push(counter.clone());
+        }
+        stack.pop();
+        assert_eq!(Arc::strong_count(&counter), 10);
+        drop(stack);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn stress() {
+        let threads = 8;
+        let per_thread = 20000;
+        let stack = Arc::new(ConcurrentStack::new());
+        let handles: Vec<_> = (0..threads).map(|t| {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                for i in 0..per_thread {
+                    stack.push(t * per_thread + i);
+                    if i % 2 == 1 {
+                        popped.extend(stack.pop());
+                        popped.extend(stack.pop());
+                    }
+                }
+                popped
+            })
+        }).collect();
+        let mut all: Vec<usize> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        while let Some(elem) = stack.pop() {
+            all.push(elem);
+        }
+        all.sort_unstable();
+        assert_eq!(all, (0..threads * per_thread).collect::<Vec<_>>());
+    }
+}
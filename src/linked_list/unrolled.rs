@@ -0,0 +1,1585 @@
+use core::cmp::Ordering;
+use core::hash::{Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use core::ptr::{self, NonNull};
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+// A singly linked list of nodes that each hold up to `B` elements inline, so that walking
+// the list follows one pointer per block instead of one per element. No node is ever empty,
+// and no two neighbouring nodes but possibly the last two would fit in one, so there are at
+// most about `2n/B` nodes.
+pub struct UnrolledList<T, const B: usize = 16> {
+    head: Link<T, B>,
+    tail: Link<T, B>,
+    len: usize,
+    _marker: PhantomData<Box<Node<T, B>>>
+}
+
+type Link<T, const B: usize> = Option<NonNull<Node<T, B>>>;
+
+struct Node<T, const B: usize> {
+    // `elems[..len]` are initialized
+    len: usize,
+    elems: [MaybeUninit<T>; B],
+    next: Link<T, B>
+}
+
+pub struct Iter<'a, T: 'a, const B: usize = 16> {
+    elems: slice::Iter<'a, T>,
+    next: Link<T, B>,
+    remaining: usize,
+    _marker: PhantomData<&'a Node<T, B>>,
+}
+
+pub struct IterMut<'a, T: 'a, const B: usize = 16> {
+    elems: slice::IterMut<'a, T>,
+    next: Link<T, B>,
+    remaining: usize,
+    _marker: PhantomData<&'a mut Node<T, B>>,
+}
+
+#[derive(Clone)]
+pub struct IntoIter<T, const B: usize = 16> {
+    list: UnrolledList<T, B>
+}
+
+// A cursor points at an element or at the "ghost" non-element between the tail and the head.
+// The ghost's index is `len`. The element sits at `offset` in the block `current`.
+pub struct Cursor<'a, T: 'a, const B: usize = 16> {
+    index: usize,
+    current: Link<T, B>,
+    offset: usize,
+    list: &'a UnrolledList<T, B>,
+}
+
+pub struct CursorMut<'a, T: 'a, const B: usize = 16> {
+    index: usize,
+    current: Link<T, B>,
+    offset: usize,
+    list: &'a mut UnrolledList<T, B>,
+}
+
+pub struct ExtractIf<'a, T: 'a, F, const B: usize = 16>
+where
+    F: FnMut(&mut T) -> bool
+{
+    list: &'a mut UnrolledList<T, B>,
+    prev: Link<T, B>,
+    cur: Link<T, B>,
+    offset: usize,
+    pred: F,
+}
+
+// Compacts the kept elements of `node_ptr` to its front. `elems[write..read]` are moved out or
+// dropped and `elems[read..end]` not visited yet; dropping this closes the gap, even on a panic.
+struct Compaction<'a, T, const B: usize> {
+    list: &'a mut UnrolledList<T, B>,
+    prev: &'a mut Link<T, B>,
+    node_ptr: NonNull<Node<T, B>>,
+    read: usize,
+    write: usize,
+    end: usize,
+}
+
+impl<T, const B: usize> Node<T, B> {
+    fn alloc() -> NonNull<Self> {
+        // an array of `MaybeUninit` needs no initialization
+        let elems = unsafe { MaybeUninit::<[MaybeUninit<T>; B]>::uninit().assume_init() };
+        let node = Box::new(Node { len: 0, elems, next: None });
+        NonNull::from(Box::leak(node))
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.elems.as_ptr() as *const T, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.elems.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == B
+    }
+
+    // The node must not be full
+    fn insert(&mut self, at: usize, elem: T) {
+        debug_assert!(at <= self.len && self.len < B);
+        unsafe {
+            let at_ptr = self.elems.as_mut_ptr().add(at);
+            ptr::copy(at_ptr, at_ptr.add(1), self.len - at);
+            at_ptr.write(MaybeUninit::new(elem));
+        }
+        self.len += 1;
+    }
+
+    fn remove(&mut self, at: usize) -> T {
+        debug_assert!(at < self.len);
+        unsafe {
+            let at_ptr = self.elems.as_mut_ptr().add(at);
+            let elem = at_ptr.read().assume_init();
+            ptr::copy(at_ptr.add(1), at_ptr, self.len - at - 1);
+            self.len -= 1;
+            elem
+        }
+    }
+
+    // Moves the elements from `at` on into a new node linked right after this one
+    fn split_at(&mut self, at: usize) -> NonNull<Self> {
+        let mut new_ptr = Self::alloc();
+        let new = unsafe { new_ptr.as_mut() };
+        let count = self.len - at;
+        unsafe { ptr::copy_nonoverlapping(self.elems.as_ptr().add(at), new.elems.as_mut_ptr(), count) };
+        new.len = count;
+        new.next = self.next;
+        self.len = at;
+        self.next = Some(new_ptr);
+        new_ptr
+    }
+
+    // Moves every element of `other` behind the ones of this node; they must fit
+    fn absorb(&mut self, other: &mut Self) {
+        let count = other.len;
+        self.take_front(other, count);
+    }
+
+    // Moves the first `count` elements of `other` behind the ones of this node; they must fit
+    fn take_front(&mut self, other: &mut Self, count: usize) {
+        debug_assert!(self.len + count <= B && count <= other.len);
+        unsafe {
+            ptr::copy_nonoverlapping(other.elems.as_ptr(), self.elems.as_mut_ptr().add(self.len), count);
+            ptr::copy(other.elems.as_ptr().add(count), other.elems.as_mut_ptr(), other.len - count);
+        }
+        self.len += count;
+        other.len -= count;
+    }
+}
+
+impl<T, const B: usize> Drop for Node<T, B> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) };
+    }
+}
+
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn new() -> Self {
+        assert!(B >= 2, "Block size must be at least 2");
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        Iter {
+            elems: [].iter(),
+            next: self.head,
+            remaining: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, B> {
+        IterMut {
+            elems: [].iter_mut(),
+            next: self.head,
+            remaining: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    // Scans block by block rather than element by element
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        let mut cur = self.head;
+        while let Some(node_ptr) = cur {
+            let node = unsafe { &*node_ptr.as_ptr() };
+            if node.as_slice().contains(value) {
+                return true;
+            }
+            cur = node.next;
+        }
+        false
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new()
+        // drop(self) is called
+    }
+
+    // Unlinks and frees `node_ptr`, whose predecessor is `prev`
+    fn unlink_after(&mut self, prev: Link<T, B>, node_ptr: NonNull<Node<T, B>>) {
+        let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+        match prev {
+            Some(mut prev_ptr) => unsafe { prev_ptr.as_mut() }.next = node.next,
+            None => self.head = node.next,
+        }
+        if self.tail == Some(node_ptr) {
+            self.tail = prev;
+        }
+    }
+
+    // Moves the successor of `node_ptr` into it if they fit in one node
+    fn merge_next(&mut self, mut node_ptr: NonNull<Node<T, B>>) {
+        let node = unsafe { node_ptr.as_mut() };
+        if let Some(mut next_ptr) = node.next {
+            if node.len + unsafe { next_ptr.as_ref() }.len <= B {
+                node.absorb(unsafe { next_ptr.as_mut() });
+                self.unlink_after(Some(node_ptr), next_ptr);
+            }
+        }
+    }
+
+    // Tops `node_ptr` up with the elements of the blocks after it until it is full or the
+    // tail. Then it cannot fit with the block before it, which therefore need not be known.
+    fn fill(&mut self, node_ptr: NonNull<Node<T, B>>) {
+        loop {
+            let node = unsafe { &mut *node_ptr.as_ptr() };
+            let mut next_ptr = match node.next {
+                Some(next_ptr) if !node.is_full() => next_ptr,
+                _ => return,
+            };
+            let next = unsafe { next_ptr.as_mut() };
+            node.take_front(next, (B - node.len).min(next.len));
+            if next.len > 0 {
+                // the block after it shrank and may now fit with its own successor
+                return self.merge_next(next_ptr);
+            }
+            self.unlink_after(Some(node_ptr), next_ptr);
+        }
+    }
+
+    // The block and offset of the element after the one at `offset` in `current`, or of the
+    // front when `current` is the ghost
+    fn position_after(&self, current: Link<T, B>, offset: usize) -> (Link<T, B>, usize) {
+        match current {
+            None => (self.head, 0),
+            Some(node_ptr) => {
+                let node = unsafe { node_ptr.as_ref() };
+                if offset + 1 < node.len {
+                    (current, offset + 1)
+                } else {
+                    (node.next, 0)
+                }
+            }
+        }
+    }
+
+    // Restores the invariants around `node_ptr`, whose predecessor is `prev`, after it shrank
+    fn rebalance(&mut self, prev: Link<T, B>, node_ptr: NonNull<Node<T, B>>) {
+        if unsafe { node_ptr.as_ref() }.len == 0 {
+            self.unlink_after(prev, node_ptr);
+        } else {
+            self.merge_next(node_ptr);
+        }
+        if let Some(prev_ptr) = prev {
+            self.merge_next(prev_ptr);
+        }
+    }
+}
+
+impl<T, const B: usize> Drop for UnrolledList<T, B> {
+    fn drop(&mut self) {
+        let mut cur = self.head;
+        while let Some(node_ptr) = cur {
+            let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+            cur = node.next;
+            // drop(node) is called
+        }
+    }
+}
+
+// O(1) operations
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.as_slice()[0])
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node_ptr| &mut unsafe { &mut *node_ptr.as_ptr() }.as_mut_slice()[0])
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.and_then(|node_ptr| unsafe { &*node_ptr.as_ptr() }.as_slice().last())
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.and_then(|node_ptr| unsafe { &mut *node_ptr.as_ptr() }.as_mut_slice().last_mut())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T, B> {
+        Cursor { index: 0, current: self.head, offset: 0, list: self }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, B> {
+        CursorMut { index: 0, current: self.head, offset: 0, list: self }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T, B> {
+        let offset = self.tail.map_or(0, |tail_ptr| unsafe { tail_ptr.as_ref() }.len - 1);
+        Cursor { index: self.len.saturating_sub(1), current: self.tail, offset, list: self }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, B> {
+        let offset = self.tail.map_or(0, |tail_ptr| unsafe { tail_ptr.as_ref() }.len - 1);
+        CursorMut { index: self.len.saturating_sub(1), current: self.tail, offset, list: self }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        match self.head {
+            Some(mut head_ptr) if !unsafe { head_ptr.as_ref() }.is_full() => {
+                unsafe { head_ptr.as_mut() }.insert(0, elem);
+            },
+            _ => {
+                let mut node_ptr = Node::alloc();
+                let node = unsafe { node_ptr.as_mut() };
+                node.insert(0, elem);
+                node.next = self.head;
+                self.head = Some(node_ptr);
+                if self.tail.is_none() {
+                    self.tail = self.head;
+                }
+            }
+        }
+        self.len += 1;
+    }
+
+    // O(B) as the remaining elements of the first block shift down
+    pub fn pop_front(&mut self) -> Option<T> {
+        let mut head_ptr = self.head?;
+        let elem = unsafe { head_ptr.as_mut() }.remove(0);
+        self.rebalance(None, head_ptr);
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        match self.tail {
+            Some(mut tail_ptr) if !unsafe { tail_ptr.as_ref() }.is_full() => {
+                let tail = unsafe { tail_ptr.as_mut() };
+                tail.insert(tail.len, elem);
+            },
+            _ => {
+                let mut node_ptr = Node::alloc();
+                unsafe { node_ptr.as_mut() }.insert(0, elem);
+                match self.tail {
+                    Some(mut tail_ptr) => unsafe { tail_ptr.as_mut() }.next = Some(node_ptr),
+                    None => self.head = Some(node_ptr),
+                }
+                self.tail = Some(node_ptr);
+            }
+        }
+        self.len += 1;
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some(mut tail_ptr) = self.tail {
+            if other.is_empty() {
+                return;
+            }
+            unsafe { tail_ptr.as_mut() }.next = other.head.take();
+            self.tail = other.tail.take();
+            self.len += mem::replace(&mut other.len, 0);
+            self.fill(tail_ptr);
+        } else {
+            mem::swap(self, other)
+        }
+    }
+}
+
+// O(n/B) operations
+impl<T, const B: usize> UnrolledList<T, B> {
+    // The node holding position `at`, its predecessor and the offset of `at` in it.
+    // `at` must be less than `self.len`.
+    fn locate(&self, mut at: usize) -> (Link<T, B>, NonNull<Node<T, B>>, usize) {
+        let mut prev = None;
+        let mut cur = self.head.unwrap();
+        loop {
+            let node = unsafe { cur.as_ref() };
+            if at < node.len {
+                return (prev, cur, at);
+            }
+            at -= node.len;
+            prev = Some(cur);
+            cur = node.next.unwrap();
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let mut tail_ptr = self.tail?;
+        let tail = unsafe { tail_ptr.as_mut() };
+        // the block before the tail holds the element right before the tail's first one
+        let prev = (self.len - tail.len).checked_sub(1).map(|at| self.locate(at).1);
+        let elem = tail.remove(tail.len - 1);
+        self.len -= 1;
+        self.rebalance(prev, tail_ptr);
+        Some(elem)
+    }
+
+    pub fn split_off(&mut self, at: usize) -> UnrolledList<T, B> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
+        if at == 0 {
+            return mem::take(self)
+        }
+        if at == self.len {
+            return Self::new()
+        }
+        let (prev, mut node_ptr, offset) = self.locate(at);
+        let (mut last_kept, first_moved) = if offset == 0 {
+            (prev.unwrap(), node_ptr)
+        } else {
+            let new_ptr = unsafe { node_ptr.as_mut() }.split_at(offset);
+            if self.tail == Some(node_ptr) {
+                self.tail = Some(new_ptr);
+            }
+            (node_ptr, new_ptr)
+        };
+        unsafe { last_kept.as_mut() }.next = None;
+        let mut second_part = Self {
+            head: Some(first_moved),
+            tail: self.tail,
+            len: self.len - at,
+            _marker: PhantomData
+        };
+        self.tail = Some(last_kept);
+        self.len = at;
+        if offset > 0 {
+            // both halves of the split block may now fit into their neighbours
+            second_part.merge_next(first_moved);
+            if let Some(prev_ptr) = prev {
+                self.merge_next(prev_ptr);
+            }
+        }
+        second_part
+    }
+
+    pub fn get(&self, at: usize) -> Option<&T> {
+        if at >= self.len {
+            return None;
+        }
+        let (_, node_ptr, offset) = self.locate(at);
+        Some(&unsafe { &*node_ptr.as_ptr() }.as_slice()[offset])
+    }
+
+    pub fn get_mut(&mut self, at: usize) -> Option<&mut T> {
+        if at >= self.len {
+            return None;
+        }
+        let (_, node_ptr, offset) = self.locate(at);
+        Some(&mut unsafe { &mut *node_ptr.as_ptr() }.as_mut_slice()[offset])
+    }
+
+    // Splits the block at `at` in two halves first if it is full, merging each half with its
+    // outer neighbour if they fit
+    pub fn insert(&mut self, at: usize, elem: T) {
+        assert!(at <= self.len, "Cannot insert at a nonexistent index");
+        if at == self.len {
+            return self.push_back(elem);
+        }
+        let (prev, mut node_ptr, mut offset) = self.locate(at);
+        let mut split = None;
+        if unsafe { node_ptr.as_ref() }.is_full() {
+            let half = B / 2;
+            let new_ptr = unsafe { node_ptr.as_mut() }.split_at(half);
+            if self.tail == Some(node_ptr) {
+                self.tail = Some(new_ptr);
+            }
+            split = Some(new_ptr);
+            if offset > half {
+                node_ptr = new_ptr;
+                offset -= half;
+            }
+        }
+        unsafe { node_ptr.as_mut() }.insert(offset, elem);
+        self.len += 1;
+        if let Some(new_ptr) = split {
+            self.merge_next(new_ptr);
+            if let Some(prev_ptr) = prev {
+                self.merge_next(prev_ptr);
+            }
+        }
+    }
+
+    // Merges the block at `at` with its neighbours if they fit in one
+    pub fn remove(&mut self, at: usize) -> T {
+        assert!(at < self.len, "Cannot remove a nonexistent index");
+        let (prev, mut node_ptr, offset) = self.locate(at);
+        let elem = unsafe { node_ptr.as_mut() }.remove(offset);
+        self.len -= 1;
+        self.rebalance(prev, node_ptr);
+        elem
+    }
+
+    // Exchanges the elements at `a` and `b` in place
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(a < self.len && b < self.len, "Cannot swap a nonexistent index");
+        let (_, mut a_ptr, a_offset) = self.locate(a);
+        let (_, mut b_ptr, b_offset) = self.locate(b);
+        if a_ptr == b_ptr {
+            return unsafe { a_ptr.as_mut() }.as_mut_slice().swap(a_offset, b_offset);
+        }
+        mem::swap(
+            &mut unsafe { a_ptr.as_mut() }.as_mut_slice()[a_offset],
+            &mut unsafe { b_ptr.as_mut() }.as_mut_slice()[b_offset]
+        );
+    }
+}
+
+// O(n) operations
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn reverse(&mut self) {
+        let mut prev = None;
+        let mut cur = self.head;
+        while let Some(mut node_ptr) = cur {
+            let node = unsafe { node_ptr.as_mut() };
+            node.as_mut_slice().reverse();
+            cur = mem::replace(&mut node.next, prev);
+            prev = Some(node_ptr);
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+        // the last two blocks, which may fit in one, are now the first two
+        if let Some(head_ptr) = self.head {
+            self.merge_next(head_ptr);
+        }
+    }
+
+    // Lazily removes and yields the elements matching `pred`;
+    // elements not reached before the iterator is dropped are kept
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, B>
+    where
+        F: FnMut(&mut T) -> bool
+    {
+        let cur = self.head;
+        ExtractIf { list: self, prev: None, cur, offset: 0, pred }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where F: FnMut(&T) -> bool
+    {
+        self.retain_mut(|elem| f(elem))
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where F: FnMut(&mut T) -> bool
+    {
+        self.retain_by(|elem, _| f(elem))
+    }
+
+    // Keeps the elements for which `keep(elem, last_kept_elem)` holds, compacting every block
+    // in place and merging it into the block before it once they fit. Elements not visited
+    // yet are kept if `keep` or a drop panics.
+    fn retain_by<F>(&mut self, mut keep: F)
+    where F: FnMut(&mut T, Option<&mut T>) -> bool
+    {
+        let mut prev = None;
+        let mut cur = self.head;
+        while let Some(mut node_ptr) = cur {
+            let node = unsafe { node_ptr.as_mut() };
+            cur = node.next;
+            let end = mem::replace(&mut node.len, 0);
+            let elems = node.elems.as_mut_ptr() as *mut T;
+            let mut block = Compaction { list: self, prev: &mut prev, node_ptr, read: 0, write: 0, end };
+            while block.read < block.end {
+                let elem = unsafe { &mut *elems.add(block.read) };
+                let last_kept = match block.write {
+                    0 => block.prev.map(|mut prev_ptr| {
+                        unsafe { prev_ptr.as_mut() }.as_mut_slice().last_mut().unwrap()
+                    }),
+                    write => Some(unsafe { &mut *elems.add(write - 1) }),
+                };
+                let kept = keep(elem, last_kept);
+                block.read += 1;
+                if kept {
+                    unsafe { ptr::copy(elems.add(block.read - 1), elems.add(block.write), 1) };
+                    block.write += 1;
+                } else {
+                    block.list.len -= 1;
+                    unsafe { ptr::drop_in_place(elem) };
+                }
+            }
+        }
+    }
+
+    pub fn remove_first(&mut self, value: &T) -> Option<T>
+    where T: PartialEq<T>
+    {
+        self.extract_if(|elem| *elem == *value).next()
+    }
+
+    // Removes consecutive elements for which `same_bucket(elem, previous_kept_elem)` holds
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where F: FnMut(&mut T, &mut T) -> bool
+    {
+        self.retain_by(|elem, last_kept| match last_kept {
+            Some(kept) => !same_bucket(elem, kept),
+            None => true,
+        })
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq<K>,
+        F: FnMut(&mut T) -> K
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    pub fn dedup(&mut self)
+    where T: PartialEq<T>
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+impl<T, const B: usize> IntoIterator for UnrolledList<T, B> {
+    type Item = T;
+    type IntoIter = IntoIter<T, B>;
+
+    fn into_iter(self) -> IntoIter<T, B> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a UnrolledList<T, B> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Iter<'a, T, B> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a mut UnrolledList<T, B> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, B>;
+
+    fn into_iter(self) -> IterMut<'a, T, B> {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug, const B: usize> fmt::Debug for UnrolledList<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Hash, const B: usize> Hash for UnrolledList<T, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T: PartialEq, const B: usize> PartialEq for UnrolledList<T, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const B: usize> Eq for UnrolledList<T, B> {}
+
+impl<T: PartialOrd, const B: usize> PartialOrd for UnrolledList<T, B> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, const B: usize> Ord for UnrolledList<T, B> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T, const B: usize> Index<usize> for UnrolledList<T, B> {
+    type Output = T;
+
+    fn index(&self, at: usize) -> &T {
+        self.get(at).expect("Cannot access a nonexistent index")
+    }
+}
+
+impl<T, const B: usize> IndexMut<usize> for UnrolledList<T, B> {
+    fn index_mut(&mut self, at: usize) -> &mut T {
+        self.get_mut(at).expect("Cannot access a nonexistent index")
+    }
+}
+
+impl<T, const B: usize> Extend<T> for UnrolledList<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: 'a + Copy, const B: usize> Extend<&'a T> for UnrolledList<T, B> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for UnrolledList<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone, const B: usize> Clone for UnrolledList<T, B> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const B: usize> Default for UnrolledList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send, const B: usize> Send for UnrolledList<T, B> {}
+
+unsafe impl<T: Sync, const B: usize> Sync for UnrolledList<T, B> {}
+
+impl<'a, T, const B: usize> Iterator for Iter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                self.remaining -= 1;
+                return Some(elem);
+            }
+            let node = unsafe { &*self.next?.as_ptr() };
+            self.elems = node.as_slice().iter();
+            self.next = node.next;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const B: usize> ExactSizeIterator for Iter<'a, T, B> {}
+
+impl<'a, T, const B: usize> FusedIterator for Iter<'a, T, B> {}
+
+impl<'a, T, const B: usize> Clone for Iter<'a, T, B> {
+    fn clone(&self) -> Self {
+        Iter { elems: self.elems.clone(), next: self.next, remaining: self.remaining, _marker: PhantomData }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug, const B: usize> fmt::Debug for Iter<'a, T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").finish()
+    }
+}
+
+unsafe impl<'a, T: Sync, const B: usize> Send for Iter<'a, T, B> {}
+
+unsafe impl<'a, T: Sync, const B: usize> Sync for Iter<'a, T, B> {}
+
+impl<'a, T, const B: usize> Iterator for IterMut<'a, T, B> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                self.remaining -= 1;
+                return Some(elem);
+            }
+            let node = unsafe { &mut *self.next?.as_ptr() };
+            self.next = node.next;
+            self.elems = node.as_mut_slice().iter_mut();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const B: usize> ExactSizeIterator for IterMut<'a, T, B> {}
+
+impl<'a, T, const B: usize> FusedIterator for IterMut<'a, T, B> {}
+
+impl<'a, T: 'a + fmt::Debug, const B: usize> fmt::Debug for IterMut<'a, T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IterMut").finish()
+    }
+}
+
+unsafe impl<'a, T: Send, const B: usize> Send for IterMut<'a, T, B> {}
+
+unsafe impl<'a, T: Sync, const B: usize> Sync for IterMut<'a, T, B> {}
+
+impl<T, const B: usize> Iterator for IntoIter<T, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for IntoIter<T, B> {}
+
+impl<T, const B: usize> FusedIterator for IntoIter<T, B> {}
+
+impl<'a, T, const B: usize> Cursor<'a, T, B> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        self.index = match self.current {
+            None => 0,
+            Some(_) => self.index + 1,
+        };
+        let (next, offset) = self.list.position_after(self.current, self.offset);
+        self.current = next;
+        self.offset = offset;
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|node_ptr| &unsafe { &*node_ptr.as_ptr() }.as_slice()[self.offset])
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let (next, offset) = self.list.position_after(self.current, self.offset);
+        next.map(|next_ptr| &unsafe { &*next_ptr.as_ptr() }.as_slice()[offset])
+    }
+}
+
+impl<'a, T, const B: usize> Clone for Cursor<'a, T, B> {
+    fn clone(&self) -> Self {
+        Cursor { index: self.index, current: self.current, offset: self.offset, list: self.list }
+    }
+}
+
+impl<'a, T: fmt::Debug, const B: usize> fmt::Debug for Cursor<'a, T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cursor").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+unsafe impl<'a, T: Sync, const B: usize> Send for Cursor<'a, T, B> {}
+
+unsafe impl<'a, T: Sync, const B: usize> Sync for Cursor<'a, T, B> {}
+
+impl<'a, T, const B: usize> CursorMut<'a, T, B> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        self.index = match self.current {
+            None => 0,
+            Some(_) => self.index + 1,
+        };
+        let (next, offset) = self.list.position_after(self.current, self.offset);
+        self.current = next;
+        self.offset = offset;
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node_ptr| &mut unsafe { &mut *node_ptr.as_ptr() }.as_mut_slice()[self.offset])
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let (next, offset) = self.list.position_after(self.current, self.offset);
+        next.map(|next_ptr| &mut unsafe { &mut *next_ptr.as_ptr() }.as_mut_slice()[offset])
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T, B> {
+        Cursor { index: self.index, current: self.current, offset: self.offset, list: self.list }
+    }
+
+    // Inserts after the current element, or at the front when at the ghost. A full block
+    // hands its last element on to the next block, or to a new one if that is full too.
+    pub fn insert_after(&mut self, elem: T) {
+        let mut node_ptr = match self.current {
+            Some(node_ptr) => node_ptr,
+            None => {
+                self.list.push_front(elem);
+                self.index = self.list.len;
+                return;
+            }
+        };
+        let node = unsafe { node_ptr.as_mut() };
+        let at = self.offset + 1;
+        let overflow = if !node.is_full() {
+            node.insert(at, elem);
+            None
+        } else if at == B {
+            Some(elem)
+        } else {
+            let last = node.remove(B - 1);
+            node.insert(at, elem);
+            Some(last)
+        };
+        if let Some(overflow) = overflow {
+            match node.next {
+                Some(mut next_ptr) if !unsafe { next_ptr.as_ref() }.is_full() => {
+                    unsafe { next_ptr.as_mut() }.insert(0, overflow);
+                },
+                _ => {
+                    let mut new_ptr = node.split_at(B);
+                    unsafe { new_ptr.as_mut() }.insert(0, overflow);
+                    if self.list.tail == self.current {
+                        self.list.tail = Some(new_ptr);
+                    }
+                }
+            }
+        }
+        self.list.len += 1;
+    }
+
+    // Removes the element after the current one, or the front when at the ghost. The current
+    // block is refilled from the ones after it, so the blocks before it are left alone.
+    pub fn remove_next(&mut self) -> Option<T> {
+        let mut node_ptr = match self.current {
+            Some(node_ptr) => node_ptr,
+            None => {
+                let ans = self.list.pop_front();
+                self.index = self.list.len;
+                return ans;
+            }
+        };
+        let node = unsafe { node_ptr.as_mut() };
+        if self.offset + 1 < node.len {
+            let elem = node.remove(self.offset + 1);
+            self.list.len -= 1;
+            self.list.fill(node_ptr);
+            Some(elem)
+        } else {
+            let mut next_ptr = node.next?;
+            let elem = unsafe { next_ptr.as_mut() }.remove(0);
+            self.list.len -= 1;
+            self.list.rebalance(Some(node_ptr), next_ptr);
+            Some(elem)
+        }
+    }
+
+    // Splits off everything after the current element, or the whole list when at the ghost
+    pub fn split_after(&mut self) -> UnrolledList<T, B> {
+        let mut node_ptr = match self.current {
+            Some(node_ptr) => node_ptr,
+            None => {
+                self.index = 0;
+                return self.list.split_off(0);
+            }
+        };
+        let node = unsafe { node_ptr.as_mut() };
+        if self.offset + 1 < node.len {
+            let new_ptr = node.split_at(self.offset + 1);
+            if self.list.tail == self.current {
+                self.list.tail = Some(new_ptr);
+            }
+        }
+        let head_ptr = match node.next.take() {
+            Some(head_ptr) => head_ptr,
+            None => return UnrolledList::new(),
+        };
+        let mut second_part = UnrolledList {
+            head: Some(head_ptr),
+            tail: self.list.tail,
+            len: self.list.len - self.index - 1,
+            _marker: PhantomData
+        };
+        self.list.tail = self.current;
+        self.list.len = self.index + 1;
+        second_part.merge_next(head_ptr);
+        second_part
+    }
+
+    // Moves all of `other` after the current element, or to the front when at the ghost
+    pub fn splice_after(&mut self, mut other: UnrolledList<T, B>) {
+        let mut other_tail_ptr = match other.tail {
+            Some(tail_ptr) => tail_ptr,
+            None => return,
+        };
+        let mut node_ptr = match self.current {
+            Some(node_ptr) => node_ptr,
+            None => {
+                other.append(self.list);
+                self.list.append(&mut other);
+                self.index = self.list.len;
+                return;
+            }
+        };
+        let node = unsafe { node_ptr.as_mut() };
+        if self.offset + 1 < node.len {
+            let new_ptr = node.split_at(self.offset + 1);
+            if self.list.tail == self.current {
+                self.list.tail = Some(new_ptr);
+            }
+        }
+        let next = mem::replace(&mut node.next, other.head.take());
+        unsafe { other_tail_ptr.as_mut() }.next = next;
+        if next.is_none() {
+            self.list.tail = Some(other_tail_ptr);
+        }
+        self.list.len += mem::replace(&mut other.len, 0);
+        other.tail = None;
+        // the other list's last two blocks may fit in one, so its tail is filled up as well
+        self.list.fill(other_tail_ptr);
+        self.list.fill(node_ptr);
+    }
+}
+
+impl<'a, T: fmt::Debug, const B: usize> fmt::Debug for CursorMut<'a, T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+unsafe impl<'a, T: Send, const B: usize> Send for CursorMut<'a, T, B> {}
+
+unsafe impl<'a, T: Sync, const B: usize> Sync for CursorMut<'a, T, B> {}
+
+impl<'a, T, const B: usize> Drop for Compaction<'a, T, B> {
+    fn drop(&mut self) {
+        let node = unsafe { self.node_ptr.as_mut() };
+        let elems = node.elems.as_mut_ptr();
+        unsafe { ptr::copy(elems.add(self.read), elems.add(self.write), self.end - self.read) };
+        node.len = self.write + self.end - self.read;
+        match *self.prev {
+            _ if node.len == 0 => self.list.unlink_after(*self.prev, self.node_ptr),
+            Some(prev_ptr) if unsafe { prev_ptr.as_ref() }.len + node.len <= B => {
+                self.list.merge_next(prev_ptr);
+            },
+            _ => *self.prev = Some(self.node_ptr),
+        }
+        if std::thread::panicking() {
+            // the block after it is left unvisited, and may now fit with it
+            if let Some(prev_ptr) = *self.prev {
+                self.list.merge_next(prev_ptr);
+            }
+        }
+    }
+}
+
+impl<'a, T, F, const B: usize> Iterator for ExtractIf<'a, T, F, B>
+where
+    F: FnMut(&mut T) -> bool
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(mut node_ptr) = self.cur {
+            let node = unsafe { node_ptr.as_mut() };
+            if self.offset == node.len {
+                // the block is done, and may now fit into the one before it
+                self.cur = node.next;
+                self.offset = 0;
+                match self.prev {
+                    Some(prev_ptr) if unsafe { prev_ptr.as_ref() }.len + node.len <= B => {
+                        self.list.merge_next(prev_ptr);
+                    },
+                    _ => self.prev = Some(node_ptr),
+                }
+                continue;
+            }
+            if (self.pred)(&mut node.as_mut_slice()[self.offset]) {
+                let elem = node.remove(self.offset);
+                self.list.len -= 1;
+                if node.len == 0 {
+                    self.cur = node.next;
+                    self.list.unlink_after(self.prev, node_ptr);
+                }
+                return Some(elem);
+            }
+            self.offset += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+impl<'a, T, F, const B: usize> Drop for ExtractIf<'a, T, F, B>
+where
+    F: FnMut(&mut T) -> bool
+{
+    fn drop(&mut self) {
+        // only the block the iterator stopped in can have shrunk without being merged yet
+        if let Some(node_ptr) = self.cur {
+            self.list.rebalance(self.prev, node_ptr);
+        }
+    }
+}
+
+impl<'a, T, F, const B: usize> FusedIterator for ExtractIf<'a, T, F, B>
+where
+    F: FnMut(&mut T) -> bool
+{}
+
+impl<'a, T: 'a + fmt::Debug, F, const B: usize> fmt::Debug for ExtractIf<'a, T, F, B>
+where
+    F: FnMut(&mut T) -> bool
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ExtractIf").field(&self.list).finish()
+    }
+}
+
+#[cfg(test)]
+impl<T, const B: usize> UnrolledList<T, B> {
+    fn check_invariants(&self) {
+        let mut cur = self.head;
+        let mut last = None;
+        let mut len = 0;
+        while let Some(node_ptr) = cur {
+            let node = unsafe { node_ptr.as_ref() };
+            assert!(node.len > 0 && node.len <= B);
+            match node.next {
+                Some(next_ptr) if node.next != self.tail => {
+                    assert!(node.len + unsafe { next_ptr.as_ref() }.len > B);
+                },
+                _ => {}
+            }
+            len += node.len;
+            last = cur;
+            cur = node.next;
+        }
+        assert_eq!(len, self.len);
+        assert_eq!(last, self.tail);
+    }
+
+    fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut cur = self.head;
+        while let Some(node_ptr) = cur {
+            count += 1;
+            cur = unsafe { node_ptr.as_ref() }.next;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Xoshiro256StarStar;
+    use std::collections::VecDeque;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn blocks_split_and_merge() {
+        let mut list: UnrolledList<u32, 4> = (0..8).collect();
+        assert_eq!(list.node_count(), 2);
+        list.insert(1, 100);
+        list.check_invariants();
+        assert_eq!(list.node_count(), 3);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 100, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!((list[2], list[4], list[8]), (1, 3, 7));
+        list[8] += 10;
+        assert_eq!(list.remove(1), 100);
+        assert_eq!(list.remove(0), 0);
+        list.check_invariants();
+        // the first two blocks merged as soon as they fit in one
+        assert_eq!(list.node_count(), 2);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 17]);
+        assert!(list.contains(&17) && !list.contains(&7));
+        list.reverse();
+        list.check_invariants();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![17, 6, 5, 4, 3, 2, 1]);
+        list.retain(|x| x % 2 == 1);
+        assert_eq!(format!("{:?}", list), "[17, 5, 3, 1]");
+    }
+
+    #[test]
+    fn append_and_split_off_merge_small_blocks() {
+        let mut list: UnrolledList<u32, 4> = UnrolledList::new();
+        for x in 0..100 {
+            let mut single: UnrolledList<u32, 4> = Some(x).into_iter().collect();
+            list.append(&mut single);
+            list.check_invariants();
+        }
+        assert_eq!(list.node_count(), 25);
+        let mut parts = Vec::new();
+        while list.len() > 1 {
+            parts.push(list.split_off(list.len().saturating_sub(3)));
+            list.check_invariants();
+        }
+        for mut part in parts.into_iter().rev() {
+            part.check_invariants();
+            list.append(&mut part);
+        }
+        list.check_invariants();
+        assert!(list.iter().cloned().eq(0..100));
+        assert!(list.node_count() <= 100 / 2);
+    }
+
+    fn to_vec<const B: usize>(list: &UnrolledList<u32, B>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn cursor() {
+        let list: UnrolledList<u32, 2> = (1..=3).collect();
+        let mut cursor = list.cursor_front();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (Some(0), Some(&1), Some(&2)));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (Some(2), Some(&3), None));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current(), cursor.peek_next()), (None, None, Some(&1)));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(list.cursor_back().current(), Some(&3));
+        let empty: UnrolledList<u32, 2> = UnrolledList::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+    }
+
+    #[test]
+    fn cursor_mut_edits() {
+        let mut list: UnrolledList<u32, 4> = (1..=5).collect();
+        let mut cursor = list.cursor_front_mut();
+        // the first block is full, so its last element moves on to the next block
+        cursor.insert_after(10);
+        *cursor.current().unwrap() = 0;
+        assert_eq!(cursor.peek_next(), Some(&mut 10));
+        cursor.move_next();
+        assert_eq!(cursor.remove_next(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.as_cursor().index(), Some(2));
+        assert_eq!(cursor.remove_next(), Some(4));
+        assert_eq!(cursor.remove_next(), Some(5));
+        assert_eq!(cursor.remove_next(), None);
+        cursor.insert_after(4);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(99);
+        cursor.remove_next();
+        cursor.insert_after(5);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![5, 0, 10, 3, 4]);
+        assert_eq!(list.back(), Some(&4));
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_next(), None);
+        cursor.insert_after(6);
+        list.check_invariants();
+        assert_eq!(list.back(), Some(&6));
+    }
+
+    #[test]
+    fn cursor_mut_split_splice() {
+        let mut list: UnrolledList<u32, 4> = (0..10).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        let mut second = cursor.split_after();
+        cursor.splice_after((10..16).collect());
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_after(UnrolledList::new());
+        cursor.splice_after((20..21).collect());
+        list.check_invariants();
+        second.check_invariants();
+        assert_eq!(to_vec(&list), vec![0, 1, 10, 11, 20, 12, 13, 14, 15]);
+        assert_eq!(to_vec(&second), (2..10).collect::<Vec<_>>());
+
+        let mut cursor = second.cursor_back_mut();
+        cursor.move_next();
+        cursor.splice_after((7..9).collect());
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 7));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.split_after().len(), 7);
+        second.check_invariants();
+        assert_eq!(to_vec(&second), vec![7, 8, 2]);
+        let mut cursor = second.cursor_back_mut();
+        cursor.move_next();
+        assert_eq!(cursor.split_after().len(), 3);
+        assert!(second.is_empty());
+        second.check_invariants();
+        // the cursor leaves the last two blocks [5, 6, 7] and [8] apart, and reversing the
+        // list turns them into the first two
+        let mut list: UnrolledList<u32, 4> = (0..12).collect();
+        list.remove(4);
+        let mut cursor = list.cursor_front_mut();
+        for _ in 0..7 {
+            cursor.move_next();
+        }
+        assert_eq!(cursor.split_after().len(), 3);
+        list.check_invariants();
+        assert_eq!(list.node_count(), 3);
+        list.reverse();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![8, 7, 6, 5, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn cursor_mut_sweeps() {
+        let mut list: UnrolledList<u32, 4> = (0..200).collect();
+        let mut cursor = list.cursor_front_mut();
+        while cursor.index().is_some() {
+            cursor.remove_next();
+            cursor.move_next();
+        }
+        list.check_invariants();
+        assert!(list.iter().cloned().eq((0..200).step_by(2)));
+        let mut cursor = list.cursor_front_mut();
+        while let Some(&mut x) = cursor.current() {
+            cursor.insert_after(x + 1);
+            cursor.move_next();
+            cursor.move_next();
+        }
+        list.check_invariants();
+        assert!(list.iter().cloned().eq(0..200));
+        assert!(list.node_count() <= 200 / 2);
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.index(), Some(199));
+        cursor.move_next();
+        for _ in 0..150 {
+            cursor.remove_next();
+        }
+        list.check_invariants();
+        assert!(list.iter().cloned().eq(150..200));
+    }
+
+    #[test]
+    fn filtering() {
+        let mut list: UnrolledList<u32, 4> = (0..20).collect();
+        list.retain(|&x| x % 3 != 0);
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x != 80
+        });
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![10, 20, 40, 50, 70, 100, 110, 130, 140, 160, 170, 190]);
+        {
+            let mut tens = list.extract_if(|x| *x % 20 == 10);
+            assert_eq!(tens.next(), Some(10));
+            assert_eq!(tens.next(), Some(50));
+            assert_eq!(tens.next(), Some(70));
+        }
+        list.check_invariants();
+        assert_eq!(list.extract_if(|x| *x > 150).collect::<Vec<_>>(), vec![160, 170, 190]);
+        list.check_invariants();
+        assert_eq!(list.remove_first(&110), Some(110));
+        assert_eq!(list.remove_first(&110), None);
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![20, 40, 100, 130, 140]);
+
+        let mut list: UnrolledList<u32, 4> = vec![1, 1, 2, 3, 3, 3, 1, 4, 4].into_iter().collect();
+        list.dedup();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![1, 2, 3, 1, 4]);
+        list.dedup_by_key(|x| *x / 2);
+        assert_eq!(to_vec(&list), vec![1, 2, 1, 4]);
+    }
+
+    #[test]
+    fn filtering_panics_keep_unvisited_elements() {
+        // the blocks are [0, 1, 100] [2, 3] [4, 5, 6, 7] [8, 9, 10, 11]; the first one shrinks
+        // to [0, 100] before the panic and then fits with the unvisited [2, 3]
+        let mut list: UnrolledList<u32, 4> = (0..12).collect();
+        list.insert(2, 100);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            list.retain(|&x| if x == 100 { panic!("Simulated panic") } else { x != 1 })
+        }));
+        assert!(result.is_err());
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![0, 100, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+
+        // panics when dropped if its flag is set
+        struct Fragile(u32, bool);
+
+        impl Drop for Fragile {
+            fn drop(&mut self) {
+                if self.1 {
+                    self.1 = false;
+                    panic!("Simulated drop panic");
+                }
+            }
+        }
+
+        let mut list: UnrolledList<Fragile, 4> = [0, 1, 1, 2, 3, 3, 4, 5, 5, 6, 7, 7].iter()
+            .enumerate()
+            .map(|(i, &x)| Fragile(x, i == 5))
+            .collect();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| list.dedup_by(|a, b| a.0 == b.0)));
+        assert!(result.is_err());
+        list.check_invariants();
+        assert_eq!(list.iter().map(|x| x.0).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 5, 6, 7, 7]);
+    }
+
+    #[test]
+    fn swap() {
+        let len = 9;
+        for a in 0..len {
+            for b in 0..len {
+                let mut list: UnrolledList<u32, 4> = (0..len as u32).collect();
+                list.swap(a, b);
+                let mut expected: Vec<u32> = (0..len as u32).collect();
+                expected.swap(a, b);
+                assert_eq!(to_vec(&list), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot swap a nonexistent index")]
+    fn invalid_swap() {
+        let mut list: UnrolledList<u32, 4> = (0..3).collect();
+        list.swap(3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Block size must be at least 2")]
+    fn block_size_one() {
+        UnrolledList::<u32, 1>::new();
+    }
+
+    fn random_operations<const B: usize>(seed: u64) {
+        let mut rng = Xoshiro256StarStar::new(seed);
+        let mut list: UnrolledList<usize, B> = UnrolledList::new();
+        let mut model = VecDeque::new();
+        for step in 0..20000 {
+            match rng.gen_index(16) {
+                0 => {
+                    list.push_back(step);
+                    model.push_back(step);
+                },
+                1 => {
+                    list.push_front(step);
+                    model.push_front(step);
+                },
+                2 | 3 => {
+                    let at = rng.gen_index(model.len() + 1);
+                    list.insert(at, step);
+                    model.insert(at, step);
+                },
+                4 | 5 if !model.is_empty() => {
+                    let at = rng.gen_index(model.len());
+                    assert_eq!(list.remove(at), model.remove(at).unwrap());
+                },
+                6 => assert_eq!(list.pop_front(), model.pop_front()),
+                7 => assert_eq!(list.pop_back(), model.pop_back()),
+                8 => {
+                    let at = rng.gen_index(model.len() + 1);
+                    let mut second = list.split_off(at);
+                    let model_second = model.split_off(at);
+                    second.check_invariants();
+                    assert!(second.iter().eq(model_second.iter()));
+                    list.append(&mut second);
+                    model.extend(model_second);
+                },
+                9 if !model.is_empty() => {
+                    let (a, b) = (rng.gen_index(model.len()), rng.gen_index(model.len()));
+                    list.swap(a, b);
+                    model.swap(a, b);
+                },
+                10 | 11 => {
+                    // `at == len` leaves the cursor at the ghost
+                    let at = rng.gen_index(model.len() + 1);
+                    let mut cursor = list.cursor_front_mut();
+                    for _ in 0..at {
+                        cursor.move_next();
+                    }
+                    let ghost = at == model.len();
+                    let next = if ghost { 0 } else { at + 1 };
+                    match rng.gen_index(3) {
+                        0 => {
+                            cursor.insert_after(step);
+                            model.insert(next, step);
+                        },
+                        1 => {
+                            let expected = if next < model.len() { model.remove(next) } else { None };
+                            assert_eq!(cursor.remove_next(), expected);
+                        },
+                        _ => {
+                            let second = cursor.split_after();
+                            second.check_invariants();
+                            cursor.splice_after(second);
+                        }
+                    }
+                    assert_eq!(cursor.index(), if ghost { None } else { Some(at) });
+                    if !ghost {
+                        assert_eq!(cursor.current().copied(), model.get(at).copied());
+                    }
+                },
+                12 if rng.gen_index(4) == 0 => {
+                    let rem = rng.below(7) as usize;
+                    list.retain(|x| x % 7 != rem);
+                    list.check_invariants();
+                    list.dedup_by_key(|x| *x / 4);
+                    let mut kept: Vec<usize> = model.drain(..).filter(|x| x % 7 != rem).collect();
+                    kept.dedup_by_key(|x| *x / 4);
+                    model.extend(kept);
+                },
+                12 => {
+                    let rem = rng.below(5) as usize;
+                    let extracted: Vec<_> = list.extract_if(|x| *x % 5 == rem).take(2).collect();
+                    let mut expected = Vec::new();
+                    while expected.len() < 2 {
+                        match model.iter().position(|x| x % 5 == rem) {
+                            Some(pos) => expected.push(model.remove(pos).unwrap()),
+                            None => break,
+                        }
+                    }
+                    assert_eq!(extracted, expected);
+                },
+                13 => {
+                    list.reverse();
+                    model.make_contiguous().reverse();
+                },
+                _ if !model.is_empty() => {
+                    let at = rng.gen_index(model.len());
+                    assert_eq!(list.get(at), model.get(at));
+                    *list.get_mut(at).unwrap() += 1;
+                    model[at] += 1;
+                }
+                _ => {}
+            }
+            list.check_invariants();
+        }
+        assert!(list.iter().eq(model.iter()));
+        assert_eq!(list.into_iter().count(), model.len());
+    }
+
+    #[test]
+    fn model_conformance_for_small_blocks() {
+        crate::linked_list::testing::model::check_conformance::<UnrolledList<u32, 2>>(20, 500);
+        crate::linked_list::testing::model::check_conformance::<UnrolledList<u32, 3>>(20, 500);
+    }
+
+    #[test]
+    fn random_operations_keep_invariants() {
+        random_operations::<2>(1);
+        random_operations::<3>(2);
+        random_operations::<16>(3);
+    }
+}
@@ -0,0 +1,570 @@
+use core::cmp::Ordering;
+use core::hash::{Hasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::fmt;
+use core::ptr::NonNull;
+use core::marker::PhantomData;
+use core::mem;
+
+// A doubly linked list whose nodes store the XOR of their neighbours' addresses in a single
+// word. Walking needs the node we came from, and since the encoding is symmetric the list
+// reads the same way from either end, which makes `reverse` a swap of `head` and `tail`.
+pub struct XorLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<Box<Node<T>>>
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    // address of the previous node ^ address of the next one, a missing node being 0
+    both: usize
+}
+
+pub struct Iter<'a, T: 'a> {
+    head: Link<T>,
+    // the node before `head`, then the one after `tail`
+    before_head: Link<T>,
+    tail: Link<T>,
+    after_tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+pub struct IterMut<'a, T: 'a> {
+    head: Link<T>,
+    before_head: Link<T>,
+    tail: Link<T>,
+    after_tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+#[derive(Clone)]
+pub struct IntoIter<T> {
+    list: XorLinkedList<T>
+}
+
+fn addr<T>(link: Link<T>) -> usize {
+    link.map_or(0, |node_ptr| node_ptr.as_ptr() as usize)
+}
+
+impl<T> Node<T> {
+    // The neighbour on the other side from `from`
+    fn other(&self, from: Link<T>) -> Link<T> {
+        NonNull::new((self.both ^ addr(from)) as *mut Node<T>)
+    }
+}
+
+// Steps from `start` away from the end it sits at, returning the node reached and the one
+// walked in from
+fn walk<T>(start: NonNull<Node<T>>, steps: usize) -> (Link<T>, NonNull<Node<T>>) {
+    let mut from = None;
+    let mut cur = start;
+    for _ in 0..steps {
+        let next = unsafe { cur.as_ref() }.other(from).unwrap();
+        from = Some(cur);
+        cur = next;
+    }
+    (from, cur)
+}
+
+impl<T> XorLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            before_head: None,
+            tail: self.tail,
+            after_tail: None,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            before_head: None,
+            tail: self.tail,
+            after_tail: None,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq<T>
+    {
+        self.iter().any(|elem| elem == value)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new()
+        // drop(self) is called
+    }
+}
+
+impl<T> Drop for XorLinkedList<T> {
+    fn drop(&mut self) {
+        let mut prev = None;
+        let mut cur = self.head;
+        while let Some(node_ptr) = cur {
+            let node = unsafe { Box::from_raw(node_ptr.as_ptr()) };
+            cur = node.other(prev);
+            prev = Some(node_ptr);
+            // drop(node) is called
+        }
+    }
+}
+
+// Linking primitives, all O(1). They work on either end: `end` is the one being pushed to or
+// popped from and `other_end` the opposite one.
+impl<T> XorLinkedList<T> {
+    fn push_end(end: &mut Link<T>, other_end: &mut Link<T>, elem: T) {
+        let node = Box::new(Node { elem, both: addr(*end) });
+        let node_ptr = NonNull::new(Box::into_raw(node));
+        match *end {
+            Some(mut end_ptr) => unsafe { end_ptr.as_mut() }.both ^= addr(node_ptr),
+            None => *other_end = node_ptr,
+        }
+        *end = node_ptr;
+    }
+
+    fn pop_end(end: &mut Link<T>, other_end: &mut Link<T>) -> Option<T> {
+        end.map(|end_ptr| {
+            let node = unsafe { Box::from_raw(end_ptr.as_ptr()) };
+            let next = node.other(None);
+            match next {
+                Some(mut next_ptr) => unsafe { next_ptr.as_mut() }.both ^= addr(Some(end_ptr)),
+                None => *other_end = None,
+            }
+            *end = next;
+            node.elem
+        })
+    }
+}
+
+// O(1) operations
+impl<T> XorLinkedList<T> {
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.as_ref().map(|node_ptr| &unsafe { node_ptr.as_ref() }.elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.as_mut().map(|node_ptr| &mut unsafe { node_ptr.as_mut() }.elem)
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        Self::push_end(&mut self.head, &mut self.tail, elem);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let elem = Self::pop_end(&mut self.head, &mut self.tail)?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        Self::push_end(&mut self.tail, &mut self.head, elem);
+        self.len += 1;
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let elem = Self::pop_end(&mut self.tail, &mut self.head)?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn reverse(&mut self) {
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        let (mut tail_ptr, mut other_head_ptr) = match (self.tail, other.head) {
+            (Some(tail_ptr), Some(head_ptr)) => (tail_ptr, head_ptr),
+            (None, _) => return mem::swap(self, other),
+            (_, None) => return,
+        };
+        unsafe { tail_ptr.as_mut() }.both ^= addr(Some(other_head_ptr));
+        unsafe { other_head_ptr.as_mut() }.both ^= addr(Some(tail_ptr));
+        self.tail = other.tail.take();
+        other.head = None;
+        self.len += mem::replace(&mut other.len, 0);
+    }
+}
+
+// O(n) operations
+impl<T> XorLinkedList<T> {
+    // The adjacent nodes at `at - 1` and `at`, walking from whichever end is closer.
+    // `at` must be in `1..self.len`.
+    fn pair_at(&self, at: usize) -> (NonNull<Node<T>>, NonNull<Node<T>>) {
+        if at <= self.len / 2 {
+            let (from, node_ptr) = walk(self.head.unwrap(), at - 1);
+            (node_ptr, unsafe { node_ptr.as_ref() }.other(from).unwrap())
+        } else {
+            let (from, node_ptr) = walk(self.tail.unwrap(), self.len - 1 - at);
+            (unsafe { node_ptr.as_ref() }.other(from).unwrap(), node_ptr)
+        }
+    }
+
+    pub fn split_off(&mut self, at: usize) -> XorLinkedList<T> {
+        assert!(at <= self.len, "Cannot split off a nonexistent index");
+        if at == 0 {
+            return mem::take(self)
+        }
+        if at == self.len {
+            return Self::new()
+        }
+        let (mut last_ptr, mut first_ptr) = self.pair_at(at);
+        unsafe { last_ptr.as_mut() }.both ^= addr(Some(first_ptr));
+        unsafe { first_ptr.as_mut() }.both ^= addr(Some(last_ptr));
+        let second_part = Self {
+            head: Some(first_ptr),
+            tail: self.tail,
+            len: self.len - at,
+            _marker: PhantomData
+        };
+        self.tail = Some(last_ptr);
+        self.len = at;
+        second_part
+    }
+}
+
+impl<T> IntoIterator for XorLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a XorLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut XorLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for XorLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Hash> Hash for XorLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for XorLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for XorLinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for XorLinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for XorLinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T> Extend<T> for XorLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for XorLinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T> FromIterator<T> for XorLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> Clone for XorLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Default for XorLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send> Send for XorLinkedList<T> {}
+
+unsafe impl<T: Sync> Sync for XorLinkedList<T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|head_ptr| {
+            let node = unsafe { &*head_ptr.as_ptr() };
+            self.len -= 1;
+            self.head = node.other(self.before_head.replace(head_ptr));
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|tail_ptr| {
+            let node = unsafe { &*tail_ptr.as_ptr() };
+            self.len -= 1;
+            self.tail = node.other(self.after_tail.replace(tail_ptr));
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.len).finish()
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|head_ptr| {
+            let node = unsafe { &mut *head_ptr.as_ptr() };
+            self.len -= 1;
+            self.head = node.other(self.before_head.replace(head_ptr));
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|tail_ptr| {
+            let node = unsafe { &mut *tail_ptr.as_ptr() };
+            self.len -= 1;
+            self.tail = node.other(self.after_tail.replace(tail_ptr));
+            &mut node.elem
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for IterMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IterMut").field(&self.len).finish()
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl<T> XorLinkedList<T> {
+        // Walks the list both ways and checks that each walk ends at the other end
+        fn check_invariants(&self) {
+            for (start, end) in [(self.head, self.tail), (self.tail, self.head)] {
+                let mut from = None;
+                let mut cur = start;
+                let mut len = 0;
+                while let Some(node_ptr) = cur {
+                    len += 1;
+                    cur = unsafe { node_ptr.as_ref() }.other(from);
+                    from = Some(node_ptr);
+                }
+                assert_eq!(from, end);
+                assert_eq!(len, self.len);
+            }
+        }
+    }
+
+    fn to_vec(list: &XorLinkedList<u32>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn double_ended_iter() {
+        let mut list: XorLinkedList<u32> = (0..6).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!((iter.next(), iter.next_back()), (Some(&0), Some(&5)));
+        assert_eq!(iter.clone().rev().collect::<Vec<_>>(), vec![&4, &3, &2, &1]);
+        assert_eq!(iter.len(), 4);
+        for elem in list.iter_mut().rev().take(2) {
+            *elem += 10;
+        }
+        assert_eq!(to_vec(&list), vec![0, 1, 2, 3, 14, 15]);
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(15));
+        assert_eq!(into_iter.len(), 5);
+    }
+
+    #[test]
+    fn reverse_in_place() {
+        let mut list: XorLinkedList<u32> = (0..5).collect();
+        list.reverse();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![4, 3, 2, 1, 0]);
+        list.push_front(5);
+        list.push_back(6);
+        assert_eq!(list.pop_back(), Some(6));
+        assert_eq!(list.pop_front(), Some(5));
+        list.append(&mut (10..12).collect());
+        list.reverse();
+        list.check_invariants();
+        assert_eq!(to_vec(&list), vec![11, 10, 0, 1, 2, 3, 4]);
+        assert_eq!(list.iter().next_back(), Some(&4));
+        let mut empty = XorLinkedList::<u32>::new();
+        empty.reverse();
+        assert_eq!(empty.pop_front(), None);
+    }
+
+    #[test]
+    fn split_off_both_halves() {
+        for len in 0..8 {
+            for at in 0..=len {
+                let mut list: XorLinkedList<u32> = (0..len).collect();
+                let mut second = list.split_off(at as usize);
+                list.check_invariants();
+                second.check_invariants();
+                assert_eq!(to_vec(&list), (0..at).collect::<Vec<_>>());
+                assert_eq!(to_vec(&second), (at..len).collect::<Vec<_>>());
+                list.append(&mut second);
+                list.check_invariants();
+                second.check_invariants();
+                assert_eq!(to_vec(&list), (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn one_word_of_links() {
+        assert_eq!(mem::size_of::<Node<usize>>(), 2 * mem::size_of::<usize>());
+    }
+}
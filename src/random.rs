@@ -0,0 +1,193 @@
+// xoshiro256** by Blackman and Vigna, seeded through SplitMix64
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut split_mix = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self { s: [split_mix(), split_mix(), split_mix(), split_mix()] }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let ans = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        ans
+    }
+
+    // Uniform in `0..bound`, by rejecting the biased low end of the 128-bit product
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Cannot sample from an empty range");
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let product = (self.next_u64() as u128) * (bound as u128);
+            if product as u64 >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    // Uniform in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for Xoshiro256StarStar {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_u64())
+    }
+}
+
+pub fn shuffle<T>(arr: &mut [T], rng: &mut Xoshiro256StarStar) {
+    for i in (1..arr.len()).rev() {
+        arr.swap(i, rng.gen_index(i + 1));
+    }
+}
+
+pub fn random_permutation(n: usize, rng: &mut Xoshiro256StarStar) -> Vec<usize> {
+    let mut ans: Vec<usize> = (0..n).collect();
+    shuffle(&mut ans, rng);
+    ans
+}
+
+// Reservoir sampling: `k` items chosen uniformly from an iterator of unknown length.
+// Returns all the items if there are fewer than `k`.
+pub fn sample_k<I: IntoIterator>(iter: I, k: usize, rng: &mut Xoshiro256StarStar) -> Vec<I::Item> {
+    let mut reservoir = Vec::with_capacity(k);
+    for (i, item) in iter.into_iter().enumerate() {
+        if i < k {
+            reservoir.push(item);
+        } else {
+            let j = rng.gen_index(i + 1);
+            if j < k {
+                reservoir[j] = item;
+            }
+        }
+    }
+    reservoir
+}
+
+// 0, 1, 2, ..., 2, 1, 0
+pub fn organ_pipe(n: usize) -> Vec<usize> {
+    (0..n).map(|i| i.min(n - 1 - i)).collect()
+}
+
+// 0, 1, ..., period - 1, 0, 1, ...
+pub fn sawtooth(n: usize, period: usize) -> Vec<usize> {
+    assert!(period > 0, "Sawtooth period must be positive");
+    (0..n).map(|i| i % period).collect()
+}
+
+// Musser's permutation of `0..n` that drives median-of-three quicksort to quadratic time
+pub fn median_of_three_killer(n: usize) -> Vec<usize> {
+    let m = n - n % 4;
+    let k = m / 2;
+    let mut ans = vec![0usize; n];
+    for i in 1..=k {
+        if i % 2 == 1 {
+            ans[i - 1] = i - 1;
+            ans[i] = k + i - 1;
+        }
+        ans[k + i - 1] = 2 * i - 1;
+    }
+    for (i, elem) in ans.iter_mut().enumerate().skip(m) {
+        *elem = i;
+    }
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::SinglyLinkedList;
+
+    #[test]
+    fn xoshiro256_reproducible() {
+        let mut a = Xoshiro256StarStar::new(42);
+        let mut b = Xoshiro256StarStar::new(42);
+        let mut c = Xoshiro256StarStar::new(43);
+        let xs: Vec<u64> = (&mut a).take(100).collect();
+        assert_eq!(xs, (&mut b).take(100).collect::<Vec<_>>());
+        assert_ne!(xs, (&mut c).take(100).collect::<Vec<_>>());
+        let mut rng = Xoshiro256StarStar::new(0);
+        let mut hits = [0usize; 6];
+        for _ in 0..6000 {
+            hits[rng.gen_index(6)] += 1;
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+        assert!(hits.iter().all(|&h| h > 850 && h < 1150));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot sample from an empty range")]
+    fn below_zero() {
+        Xoshiro256StarStar::new(0).below(0);
+    }
+
+    #[test]
+    fn shuffle_and_sort() {
+        let mut rng = Xoshiro256StarStar::new(7);
+        let perm = random_permutation(200, &mut rng);
+        assert_ne!(perm, (0..200).collect::<Vec<_>>());
+        for sort in [crate::sort::select_sort, crate::sort::bubble_sort, crate::sort::insert_sort].iter() {
+            let mut arr = perm.clone();
+            sort(&mut arr);
+            assert_eq!(arr, (0..200).collect::<Vec<_>>());
+        }
+        let mut empty: [u8; 0] = [];
+        shuffle(&mut empty, &mut rng);
+    }
+
+    #[test]
+    fn reservoir_sampling() {
+        let mut rng = Xoshiro256StarStar::new(1);
+        let list: SinglyLinkedList<usize> = (0..100).collect();
+        let sample = sample_k(&list, 10, &mut rng);
+        assert_eq!(sample.len(), 10);
+        assert!(sample.iter().all(|elem| list.contains(elem)));
+        assert_eq!(sample_k(list.clone(), 1000, &mut rng), (0..100).collect::<Vec<_>>());
+        let mut hits = [0usize; 5];
+        for _ in 0..5000 {
+            for elem in sample_k(0..5, 2, &mut rng) {
+                hits[elem] += 1;
+            }
+        }
+        assert!(hits.iter().all(|&h| h > 1800 && h < 2200));
+    }
+
+    #[test]
+    fn adversarial_inputs() {
+        assert_eq!(organ_pipe(7), vec![0, 1, 2, 3, 2, 1, 0]);
+        assert_eq!(organ_pipe(4), vec![0, 1, 1, 0]);
+        assert_eq!(organ_pipe(0), vec![]);
+        assert_eq!(sawtooth(7, 3), vec![0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(median_of_three_killer(8), vec![0, 4, 2, 6, 1, 3, 5, 7]);
+        for n in 0..50 {
+            let mut killer = median_of_three_killer(n);
+            killer.sort();
+            assert_eq!(killer, (0..n).collect::<Vec<_>>());
+        }
+    }
+}
@@ -1,3 +1,11 @@
+use core::cmp::{Ordering, Reverse};
+use core::mem;
+use std::collections::{BinaryHeap, VecDeque};
+
+pub mod permutation;
+
+pub use self::permutation::{next_permutation, prev_permutation};
+
 pub fn select_sort<T: Ord>(arr: &mut [T]) {
     for i in 0..arr.len() {
         let mut mi = i;
@@ -22,7 +30,7 @@ pub fn bubble_sort<T: Ord>(arr: &mut [T]) {
 
 pub fn insert_sort<T: Ord>(arr: &mut [T]) {
     for i in 0..arr.len() {
-        let tmp = unsafe { core::ptr::read(&mut arr[i]) };
+        let tmp = unsafe { core::ptr::read(&arr[i]) };
         for j in (0..i).rev() {
             if tmp < arr[j] {
                 arr.swap(j, j + 1);
@@ -38,7 +46,7 @@ where
     F: Fn(T) -> usize, 
     G: Fn(usize) -> T 
 {
-    let (mut min, mut max) = (usize::max_value(), usize::min_value());
+    let (mut min, mut max) = (usize::MAX, usize::MIN);
     for elem in arr.iter() {
         let mapped = discrete(unsafe { core::ptr::read(elem) });
         if mapped < min {
@@ -48,7 +56,7 @@ where
             max = mapped
         }
     }
-    if max == usize::min_value() && min == usize::max_value() {
+    if max == usize::MIN && min == usize::MAX {
         return; // empty array, nothing to do
     }
     let mut cnt = vec![0usize; max - min + 1];    
@@ -65,6 +73,379 @@ where
     }
 }
 
+pub fn count_sort_by_key<T, F>(arr: &mut [T], key: F)
+where 
+    F: Fn(&T) -> usize 
+{
+    let keys: Vec<usize> = arr.iter().map(key).collect();
+    let (min, max) = match (keys.iter().min(), keys.iter().max()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => return, // empty array, nothing to do
+    };
+    let mut pos = vec![0usize; max - min + 2];
+    for &k in keys.iter() {
+        pos[k - min + 1] += 1;
+    }
+    for i in 1..pos.len() {
+        pos[i] += pos[i - 1];
+    }
+    let mut order = vec![0usize; arr.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        order[pos[k - min]] = i;
+        pos[k - min] += 1;
+    }
+    apply_permutation(arr, order);
+}
+
+// Strings are compared byte by byte; the end of a string sorts before any byte.
+fn byte_at(key: &[u8], depth: usize) -> Option<u8> {
+    key.get(depth).cloned()
+}
+
+fn insert_sort_from<T, F>(arr: &mut [T], depth: usize, key: &F)
+where 
+    F: Fn(&T) -> &[u8] 
+{
+    for i in 1..arr.len() {
+        for j in (0..i).rev() {
+            if key(&arr[j + 1])[depth..] < key(&arr[j])[depth..] {
+                arr.swap(j, j + 1);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+const MKQS_INSERT_LIMIT: usize = 16;
+
+fn multikey_quicksort_by<T, F>(mut arr: &mut [T], mut depth: usize, key: &F)
+where 
+    F: Fn(&T) -> &[u8] 
+{
+    while arr.len() > 1 {
+        if arr.len() <= MKQS_INSERT_LIMIT {
+            insert_sort_from(arr, depth, key);
+            return;
+        }
+        arr.swap(0, arr.len() / 2);
+        let pivot = byte_at(key(&arr[0]), depth);
+        let (mut lt, mut i, mut gt) = (0, 1, arr.len());
+        while i < gt {
+            match byte_at(key(&arr[i]), depth).cmp(&pivot) {
+                Ordering::Less => {
+                    arr.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                },
+                Ordering::Greater => {
+                    gt -= 1;
+                    arr.swap(i, gt);
+                },
+                Ordering::Equal => i += 1,
+            }
+        }
+        let (less, rest) = arr.split_at_mut(lt);
+        let (equal, greater) = rest.split_at_mut(gt - lt);
+        // every string in `equal` has ended when the pivot has, so they are all the same
+        let equal: &mut [T] = if pivot.is_none() { &mut [] } else { equal };
+        // recursing only into the two smaller parts keeps the stack O(log n) deep
+        let mut parts = [(less, depth), (equal, depth + 1), (greater, depth)];
+        parts.sort_by_key(|(part, _)| part.len());
+        let [(smallest, smallest_depth), (middle, middle_depth), (largest, largest_depth)] = parts;
+        multikey_quicksort_by(smallest, smallest_depth, key);
+        multikey_quicksort_by(middle, middle_depth, key);
+        arr = largest;
+        depth = largest_depth;
+    }
+}
+
+pub fn multikey_quicksort<S: AsRef<[u8]>>(arr: &mut [S]) {
+    multikey_quicksort_by(arr, 0, &|s: &S| s.as_ref());
+}
+
+pub fn multikey_quicksort_with_lcp<S: AsRef<[u8]>>(arr: &mut [S]) -> Vec<usize> {
+    multikey_quicksort(arr);
+    lcp_array(arr)
+}
+
+const BURST_LIMIT: usize = 1024;
+
+enum BurstSlot {
+    // Bursts once it holds more than `limit` strings
+    Bucket { items: Vec<usize>, limit: usize },
+    Node(Box<BurstNode>),
+}
+
+struct BurstNode {
+    ends: Vec<usize>,
+    slots: Vec<BurstSlot>,
+}
+
+impl BurstNode {
+    fn new() -> Self {
+        Self {
+            ends: Vec::new(),
+            slots: (0..256).map(|_| BurstSlot::Bucket { items: Vec::new(), limit: BURST_LIMIT }).collect(),
+        }
+    }
+}
+
+// Long shared prefixes would nest one node per byte, so the tree is torn down with an explicit stack
+impl Drop for BurstNode {
+    fn drop(&mut self) {
+        let mut stack = vec![mem::take(&mut self.slots)];
+        while let Some(slots) = stack.pop() {
+            for slot in slots {
+                if let BurstSlot::Node(mut child) = slot {
+                    stack.push(mem::take(&mut child.slots));
+                }
+            }
+        }
+    }
+}
+
+// Distributes `bucket` into a new trie node whose strings share `depth` bytes. Oversized
+// buckets of the new node burst later, on their next insertion.
+fn burst<S: AsRef<[u8]>>(bucket: Vec<usize>, depth: usize, strs: &[S]) -> BurstNode {
+    let mut node = BurstNode::new();
+    for idx in bucket {
+        match byte_at(strs[idx].as_ref(), depth) {
+            None => node.ends.push(idx),
+            Some(byte) => if let BurstSlot::Bucket { items, .. } = &mut node.slots[byte as usize] {
+                items.push(idx)
+            },
+        }
+    }
+    node
+}
+
+fn burst_insert<S: AsRef<[u8]>>(root: &mut BurstNode, idx: usize, strs: &[S]) {
+    let key = strs[idx].as_ref();
+    let mut node = root;
+    let mut depth = 0;
+    loop {
+        let byte = match byte_at(key, depth) {
+            Some(byte) => byte,
+            None => return node.ends.push(idx),
+        };
+        let slot = &mut node.slots[byte as usize];
+        match slot {
+            BurstSlot::Node(child) => {
+                node = child;
+                depth += 1;
+            },
+            BurstSlot::Bucket { items, limit } => {
+                items.push(idx);
+                if items.len() > *limit {
+                    let next = byte_at(strs[items[0]].as_ref(), depth + 1);
+                    if items.iter().all(|&other| byte_at(strs[other].as_ref(), depth + 1) == next) {
+                        // bursting would only move all of them one byte deeper, so the
+                        // bucket grows instead and is left to the multikey quicksort
+                        *limit *= 2;
+                    } else {
+                        let items = mem::take(items);
+                        *slot = BurstSlot::Node(Box::new(burst(items, depth + 1, strs)));
+                    }
+                }
+                return;
+            },
+        }
+    }
+}
+
+fn burst_collect<S: AsRef<[u8]>>(mut root: BurstNode, strs: &[S], out: &mut Vec<usize>) {
+    out.append(&mut root.ends);
+    let mut stack = vec![(mem::take(&mut root.slots).into_iter(), 0)];
+    while let Some((slots, depth)) = stack.last_mut() {
+        let depth = *depth;
+        match slots.next() {
+            None => {
+                stack.pop();
+            },
+            Some(BurstSlot::Bucket { items, .. }) => {
+                let mut keyed: Vec<_> = items.into_iter().map(|idx| (strs[idx].as_ref(), idx)).collect();
+                multikey_quicksort_by(&mut keyed, depth + 1, &|&(key, _): &(&[u8], usize)| key);
+                out.extend(keyed.into_iter().map(|(_, idx)| idx));
+            },
+            Some(BurstSlot::Node(mut child)) => {
+                out.append(&mut child.ends);
+                stack.push((mem::take(&mut child.slots).into_iter(), depth + 1));
+            },
+        }
+    }
+}
+
+// Moves `arr[order[i]]` to `arr[i]` for every `i`, following permutation cycles
+fn apply_permutation<T>(arr: &mut [T], mut order: Vec<usize>) {
+    for i in 0..arr.len() {
+        if order[i] == usize::MAX {
+            continue;
+        }
+        let mut cur = i;
+        loop {
+            let src = mem::replace(&mut order[cur], usize::MAX);
+            if src == i {
+                break;
+            }
+            arr.swap(cur, src);
+            cur = src;
+        }
+    }
+}
+
+pub fn burstsort<S: AsRef<[u8]>>(arr: &mut [S]) {
+    let mut root = BurstNode::new();
+    for idx in 0..arr.len() {
+        burst_insert(&mut root, idx, arr);
+    }
+    let mut order = Vec::with_capacity(arr.len());
+    burst_collect(root, arr, &mut order);
+    apply_permutation(arr, order);
+}
+
+pub fn burstsort_with_lcp<S: AsRef<[u8]>>(arr: &mut [S]) -> Vec<usize> {
+    burstsort(arr);
+    lcp_array(arr)
+}
+
+// `lcp[i]` is the length of the common prefix of `sorted[i - 1]` and `sorted[i]`, `lcp[0]` is 0
+pub fn lcp_array<S: AsRef<[u8]>>(sorted: &[S]) -> Vec<usize> {
+    let mut lcp = Vec::with_capacity(sorted.len());
+    for i in 0..sorted.len() {
+        if i == 0 {
+            lcp.push(0);
+            continue;
+        }
+        let (a, b) = (sorted[i - 1].as_ref(), sorted[i].as_ref());
+        lcp.push(a.iter().zip(b).take_while(|(x, y)| x == y).count());
+    }
+    lcp
+}
+
+// Vertices are `0..graph.len()` and `graph[u]` lists the targets of the edges out of `u`.
+// Returns a topological order, or the vertices of a cycle `v0 -> v1 -> ... -> v0` when there is none.
+pub fn topological_sort<A>(graph: &[A]) -> Result<Vec<usize>, Vec<usize>>
+where 
+    for<'a> &'a A: IntoIterator<Item = &'a usize> 
+{
+    kahn(graph, VecDeque::new())
+}
+
+// Same as `topological_sort`, but picks the smallest ready vertex first
+pub fn topological_sort_lexicographic<A>(graph: &[A]) -> Result<Vec<usize>, Vec<usize>>
+where 
+    for<'a> &'a A: IntoIterator<Item = &'a usize> 
+{
+    kahn(graph, BinaryHeap::new())
+}
+
+pub fn topological_sort_dfs<A>(graph: &[A]) -> Result<Vec<usize>, Vec<usize>>
+where 
+    for<'a> &'a A: IntoIterator<Item = &'a usize> 
+{
+    let mut order = dfs_postorder(graph)?;
+    order.reverse();
+    Ok(order)
+}
+
+trait Frontier {
+    fn push(&mut self, v: usize);
+    fn pop(&mut self) -> Option<usize>;
+}
+
+impl Frontier for VecDeque<usize> {
+    fn push(&mut self, v: usize) {
+        self.push_back(v)
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.pop_front()
+    }
+}
+
+impl Frontier for BinaryHeap<Reverse<usize>> {
+    fn push(&mut self, v: usize) {
+        BinaryHeap::push(self, Reverse(v))
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        BinaryHeap::pop(self).map(|Reverse(v)| v)
+    }
+}
+
+fn kahn<A, P: Frontier>(graph: &[A], mut ready: P) -> Result<Vec<usize>, Vec<usize>>
+where 
+    for<'a> &'a A: IntoIterator<Item = &'a usize> 
+{
+    let mut indegree = vec![0usize; graph.len()];
+    for edges in graph {
+        for &v in edges {
+            indegree[v] += 1;
+        }
+    }
+    for (v, &d) in indegree.iter().enumerate() {
+        if d == 0 {
+            ready.push(v);
+        }
+    }
+    let mut order = Vec::with_capacity(graph.len());
+    while let Some(u) = ready.pop() {
+        order.push(u);
+        for &v in &graph[u] {
+            indegree[v] -= 1;
+            if indegree[v] == 0 {
+                ready.push(v);
+            }
+        }
+    }
+    if order.len() == graph.len() {
+        Ok(order)
+    } else {
+        Err(dfs_postorder(graph).expect_err("Kahn's algorithm stalled on an acyclic graph"))
+    }
+}
+
+// Iterative depth first search; returns the first cycle met on the search path
+fn dfs_postorder<'g, A>(graph: &'g [A]) -> Result<Vec<usize>, Vec<usize>>
+where 
+    for<'a> &'a A: IntoIterator<Item = &'a usize> 
+{
+    const WHITE: u8 = 0;
+    const GRAY: u8 = 1;
+    const BLACK: u8 = 2;
+    let mut color = vec![WHITE; graph.len()];
+    let mut order = Vec::with_capacity(graph.len());
+    let mut path: Vec<(usize, <&'g A as IntoIterator>::IntoIter)> = Vec::new();
+    for root in 0..graph.len() {
+        if color[root] != WHITE {
+            continue;
+        }
+        color[root] = GRAY;
+        path.push((root, graph[root].into_iter()));
+        while let Some((u, edges)) = path.last_mut() {
+            let u = *u;
+            match edges.next() {
+                Some(&v) if color[v] == WHITE => {
+                    color[v] = GRAY;
+                    path.push((v, graph[v].into_iter()));
+                },
+                Some(&v) if color[v] == GRAY => {
+                    let start = path.iter().position(|&(w, _)| w == v).unwrap();
+                    return Err(path[start..].iter().map(|&(w, _)| w).collect());
+                },
+                Some(_) => {},
+                None => {
+                    color[u] = BLACK;
+                    order.push(u);
+                    path.pop();
+                },
+            }
+        }
+    }
+    Ok(order)
+}
+
 #[cfg(test)]
 mod tests {
     macro_rules! sort_test {
@@ -90,6 +471,131 @@ fn $fn_name() {
     sort_test!(bubble_sort);
     sort_test!(insert_sort);
 
+    macro_rules! string_sort_test {
+        ($fn_name: ident, $lcp_name: ident) => {
+#[test]
+fn $fn_name() {
+    let mut arr = vec!["she", "sells", "seashells", "by", "the", "sea", "shore", "", "the", "shells", "she", "sells"];
+    super::$fn_name(&mut arr);
+    assert_eq!(arr, vec!["", "by", "sea", "seashells", "sells", "sells", "she", "she", "shells", "shore", "the", "the"]);
+    let mut arr: Vec<Vec<u8>> = vec![vec![2, 0], vec![2], vec![], vec![255, 1], vec![0, 0, 0], vec![0, 0]];
+    super::$fn_name(&mut arr);
+    assert_eq!(arr, vec![vec![], vec![0, 0], vec![0, 0, 0], vec![2], vec![2, 0], vec![255, 1]]);
+    let mut arr: Vec<String> = vec![];
+    super::$fn_name(&mut arr);
+    assert!(arr.is_empty());
+    let mut arr: Vec<String> = (0..5000u32).rev()
+        .map(|i| format!("https://example.com/{}/{}", i % 7, i.wrapping_mul(2654435761) % 1000))
+        .collect();
+    let mut expected = arr.clone();
+    expected.sort();
+    super::$fn_name(&mut arr);
+    assert_eq!(arr, expected);
+    let mut arr = vec!["aaaa"; 3000];
+    arr.push("aaa");
+    arr.push("aaaaa");
+    super::$fn_name(&mut arr);
+    assert_eq!(arr[0], "aaa");
+    assert_eq!(arr[3001], "aaaaa");
+    let mut arr = vec!["banana", "band", "ban", "apple", "bandana"];
+    let lcp = super::$lcp_name(&mut arr);
+    assert_eq!(arr, vec!["apple", "ban", "banana", "band", "bandana"]);
+    assert_eq!(lcp, vec![0, 0, 3, 3, 4]);
+}
+        };
+    }
+    #[test]
+    fn count_sort_by_key() {
+        let mut arr = vec![(3, 'a'), (1, 'b'), (3, 'c'), (0, 'd'), (1, 'e')];
+        super::count_sort_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, vec![(0, 'd'), (1, 'b'), (1, 'e'), (3, 'a'), (3, 'c')]);
+        let mut arr: Vec<(usize, char)> = vec![];
+        super::count_sort_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, vec![]);
+    }
+
+    string_sort_test!(multikey_quicksort, multikey_quicksort_with_lcp);
+    string_sort_test!(burstsort, burstsort_with_lcp);
+
+    // Runs on a small stack to catch recursion that grows with the prefix length
+    #[test]
+    fn long_shared_prefixes() {
+        let sort = || {
+            let prefix = "x".repeat(10_000);
+            let mut arr: Vec<String> = (0..1100u32).rev().map(|i| format!("{}{}", prefix, i)).collect();
+            // each of these branches off the shared run one byte later than the one before
+            arr.extend((0..1500).map(|i| format!("{}b", "y".repeat(i))));
+            let mut expected = arr.clone();
+            expected.sort();
+            let mut burst = arr.clone();
+            super::burstsort(&mut burst);
+            assert_eq!(burst, expected);
+            super::multikey_quicksort(&mut arr);
+            assert_eq!(arr, expected);
+        };
+        std::thread::Builder::new().stack_size(256 * 1024).spawn(sort).unwrap().join().unwrap();
+    }
+
+    fn check_topological<A>(graph: &[A], order: &[usize])
+    where 
+        for<'a> &'a A: IntoIterator<Item = &'a usize> 
+    {
+        let mut pos = vec![usize::MAX; graph.len()];
+        for (i, &v) in order.iter().enumerate() {
+            assert_eq!(pos[v], usize::MAX);
+            pos[v] = i;
+        }
+        assert_eq!(order.len(), graph.len());
+        for (u, edges) in graph.iter().enumerate() {
+            for &v in edges {
+                assert!(pos[u] < pos[v]);
+            }
+        }
+    }
+
+    fn check_cycle<A>(graph: &[A], cycle: &[usize])
+    where 
+        for<'a> &'a A: IntoIterator<Item = &'a usize> 
+    {
+        assert!(!cycle.is_empty());
+        for i in 0..cycle.len() {
+            let (u, v) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+            assert!(graph[u].into_iter().any(|&w| w == v));
+        }
+    }
+
+    macro_rules! topological_sort_test {
+        ($fn_name: ident) => {
+#[test]
+fn $fn_name() {
+    use crate::linked_list::SinglyLinkedList;
+    let graph = vec![vec![3], vec![3, 4], vec![0, 1], vec![], vec![3], vec![]];
+    check_topological(&graph, &super::$fn_name(&graph).unwrap());
+    let list_graph: Vec<SinglyLinkedList<usize>> = graph.iter().map(|edges| edges.iter().cloned().collect()).collect();
+    check_topological(&list_graph, &super::$fn_name(&list_graph).unwrap());
+    let empty: Vec<Vec<usize>> = vec![];
+    assert_eq!(super::$fn_name(&empty), Ok(vec![]));
+    let cyclic = vec![vec![1], vec![2], vec![3, 4], vec![1], vec![]];
+    let cycle = super::$fn_name(&cyclic).unwrap_err();
+    check_cycle(&cyclic, &cycle);
+    assert_eq!(cycle.len(), 3);
+    let self_loop = vec![vec![], vec![1]];
+    assert_eq!(super::$fn_name(&self_loop), Err(vec![1]));
+}
+        };
+    }
+    topological_sort_test!(topological_sort);
+    topological_sort_test!(topological_sort_dfs);
+    topological_sort_test!(topological_sort_lexicographic);
+
+    #[test]
+    fn lexicographic_order() {
+        let graph = vec![vec![3], vec![3, 4], vec![0, 1], vec![], vec![3], vec![]];
+        assert_eq!(super::topological_sort_lexicographic(&graph), Ok(vec![2, 0, 1, 4, 3, 5]));
+        let chain = vec![vec![], vec![0], vec![1], vec![2]];
+        assert_eq!(super::topological_sort_lexicographic(&chain), Ok(vec![3, 2, 1, 0]));
+    }
+
     #[test]
     fn count_sort() {
         let mut arr = vec![1,2,2,2,1,1,1,2,2,2,2,2,1,1,2,2];
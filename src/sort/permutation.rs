@@ -0,0 +1,245 @@
+use core::iter::FusedIterator;
+
+// Rearranges `arr` into the next permutation in lexicographic order.
+// Returns false and leaves `arr` sorted when it was already the last one.
+pub fn next_permutation<T: Ord>(arr: &mut [T]) -> bool {
+    let pivot = match (1..arr.len()).rev().find(|&i| arr[i - 1] < arr[i]) {
+        Some(i) => i - 1,
+        None => {
+            arr.reverse();
+            return false;
+        }
+    };
+    let succ = (pivot + 1..arr.len()).rev().find(|&i| arr[pivot] < arr[i]).unwrap();
+    arr.swap(pivot, succ);
+    arr[pivot + 1..].reverse();
+    true
+}
+
+// Rearranges `arr` into the previous permutation in lexicographic order.
+// Returns false and leaves `arr` reverse sorted when it was already the first one.
+pub fn prev_permutation<T: Ord>(arr: &mut [T]) -> bool {
+    let pivot = match (1..arr.len()).rev().find(|&i| arr[i - 1] > arr[i]) {
+        Some(i) => i - 1,
+        None => {
+            arr.reverse();
+            return false;
+        }
+    };
+    let pred = (pivot + 1..arr.len()).rev().find(|&i| arr[pivot] > arr[i]).unwrap();
+    arr.swap(pivot, pred);
+    arr[pivot + 1..].reverse();
+    true
+}
+
+// Every `k`-subset of `0..n` as increasing indices, in lexicographic order
+pub fn combinations(n: usize, k: usize) -> Combinations {
+    Combinations { 
+        n, 
+        indices: (0..k).collect(), 
+        done: k > n 
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Combinations {
+    n: usize,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let ans = self.indices.clone();
+        let k = self.indices.len();
+        match (0..k).rev().find(|&i| self.indices[i] < self.n - k + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            },
+            None => self.done = true,
+        }
+        Some(ans)
+    }
+}
+
+impl FusedIterator for Combinations {}
+
+// Heap's algorithm: each permutation differs from the previous one by a single swap
+pub fn heap_permutations<T: Clone>(items: Vec<T>) -> HeapPermutations<T> {
+    HeapPermutations { 
+        counters: vec![0; items.len()], 
+        items, 
+        i: 0, 
+        first: true 
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeapPermutations<T> {
+    items: Vec<T>,
+    counters: Vec<usize>,
+    i: usize,
+    first: bool,
+}
+
+impl<T: Clone> Iterator for HeapPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.first {
+            self.first = false;
+            return Some(self.items.clone());
+        }
+        while self.i < self.items.len() {
+            if self.counters[self.i] < self.i {
+                let j = if self.i % 2 == 0 { 0 } else { self.counters[self.i] };
+                self.items.swap(j, self.i);
+                self.counters[self.i] += 1;
+                self.i = 0;
+                return Some(self.items.clone());
+            }
+            self.counters[self.i] = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+impl<T: Clone> FusedIterator for HeapPermutations<T> {}
+
+// code[i] counts the elements after position i that are smaller than perm[i]
+pub fn lehmer_code<T: Ord>(perm: &[T]) -> Vec<usize> {
+    (0..perm.len())
+        .map(|i| perm[i + 1..].iter().filter(|elem| **elem < perm[i]).count())
+        .collect()
+}
+
+// Position of `perm` in the lexicographic order of the permutations of its (distinct) elements
+pub fn permutation_rank<T: Ord>(perm: &[T]) -> usize {
+    let code = lehmer_code(perm);
+    let n = code.len();
+    let mut rank = 0usize;
+    for (i, digit) in code.into_iter().enumerate() {
+        rank = rank.checked_mul(n - i)
+            .and_then(|rank| rank.checked_add(digit))
+            .expect("Permutation rank overflows usize");
+    }
+    rank
+}
+
+// The permutation of `0..n` with the given rank, inverse of `permutation_rank`
+pub fn permutation_unrank(n: usize, mut rank: usize) -> Vec<usize> {
+    let mut code = vec![0usize; n];
+    for i in (0..n).rev() {
+        let radix = n - i;
+        code[i] = rank % radix;
+        rank /= radix;
+    }
+    assert!(rank == 0, "Rank is not less than the number of permutations");
+    let mut unused: Vec<usize> = (0..n).collect();
+    code.into_iter().map(|digit| unused.remove(digit)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factorial(n: usize) -> usize {
+        (1..=n).product()
+    }
+
+    #[test]
+    fn next_and_prev_permutation() {
+        let mut arr = [1, 2, 3];
+        let mut seen = vec![arr];
+        while next_permutation(&mut arr) {
+            seen.push(arr);
+        }
+        assert_eq!(seen, vec![[1, 2, 3], [1, 3, 2], [2, 1, 3], [2, 3, 1], [3, 1, 2], [3, 2, 1]]);
+        assert_eq!(arr, [1, 2, 3]);
+        let mut arr = [3, 2, 1];
+        let mut back = vec![arr];
+        while prev_permutation(&mut arr) {
+            back.push(arr);
+        }
+        back.reverse();
+        assert_eq!(back, seen);
+        assert_eq!(arr, [3, 2, 1]);
+        let mut arr = [1, 1, 2];
+        let mut count = 1;
+        while next_permutation(&mut arr) {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        let mut empty: [u8; 0] = [];
+        assert!(!next_permutation(&mut empty));
+        assert!(!prev_permutation(&mut empty));
+    }
+
+    #[test]
+    fn exhaustive_sorts() {
+        for n in 0..=6 {
+            let mut perm: Vec<usize> = (0..n).collect();
+            let mut count = 0;
+            loop {
+                for sort in [crate::sort::select_sort, crate::sort::bubble_sort, crate::sort::insert_sort].iter() {
+                    let mut arr = perm.clone();
+                    sort(&mut arr);
+                    assert_eq!(arr, (0..n).collect::<Vec<_>>());
+                }
+                assert_eq!(permutation_rank(&perm), count);
+                assert_eq!(permutation_unrank(n, count), perm);
+                count += 1;
+                if !next_permutation(&mut perm) {
+                    break;
+                }
+            }
+            assert_eq!(count, factorial(n));
+        }
+    }
+
+    #[test]
+    fn heap_permutations_all_distinct() {
+        for n in 0..=6 {
+            let mut all: Vec<Vec<usize>> = heap_permutations((0..n).collect()).collect();
+            assert_eq!(all.len(), factorial(n));
+            all.sort();
+            all.dedup();
+            assert_eq!(all.len(), factorial(n));
+        }
+        let mut iter = heap_permutations(vec!['a', 'b', 'c']);
+        assert_eq!(iter.next(), Some(vec!['a', 'b', 'c']));
+        assert_eq!(iter.next(), Some(vec!['b', 'a', 'c']));
+    }
+
+    #[test]
+    fn combinations_in_order() {
+        let all: Vec<Vec<usize>> = combinations(4, 2).collect();
+        assert_eq!(all, vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2, 3]]);
+        assert_eq!(combinations(3, 0).collect::<Vec<_>>(), vec![Vec::<usize>::new()]);
+        assert_eq!(combinations(2, 3).count(), 0);
+        assert_eq!(combinations(10, 4).count(), 210);
+    }
+
+    #[test]
+    fn lehmer_rank() {
+        assert_eq!(lehmer_code(&['c', 'a', 'd', 'b']), vec![2, 0, 1, 0]);
+        assert_eq!(permutation_rank(&['c', 'a', 'd', 'b']), 13);
+        assert_eq!(permutation_unrank(4, 13), vec![2, 0, 3, 1]);
+        assert_eq!(permutation_unrank(0, 0), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rank is not less than the number of permutations")]
+    fn unrank_out_of_range() {
+        permutation_unrank(3, 6);
+    }
+}
@@ -0,0 +1,244 @@
+use core::ops::Range;
+use core::mem;
+use crate::sort::count_sort_by_key;
+
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let s: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    sa_is(&s, u8::MAX as usize)
+}
+
+// Sorts suffixes by their first 2^k bytes, doubling k with two stable counting sort passes
+pub fn suffix_array_doubling(text: &[u8]) -> Vec<usize> {
+    let n = text.len();
+    let mut sa: Vec<usize> = (0..n).collect();
+    if n == 0 {
+        return sa;
+    }
+    let mut rank: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    let mut next_rank = vec![0usize; n];
+    count_sort_by_key(&mut sa, |&i| rank[i]);
+    let mut k = 1;
+    while k < n {
+        let second = |rank: &[usize], i: usize| rank.get(i + k).map_or(0, |r| r + 1);
+        count_sort_by_key(&mut sa, |&i| second(&rank, i));
+        count_sort_by_key(&mut sa, |&i| rank[i]);
+        next_rank[sa[0]] = 0;
+        for j in 1..n {
+            let (a, b) = (sa[j - 1], sa[j]);
+            let differs = (rank[a], second(&rank, a)) != (rank[b], second(&rank, b));
+            next_rank[b] = next_rank[a] + differs as usize;
+        }
+        mem::swap(&mut rank, &mut next_rank);
+        if rank[sa[n - 1]] == n - 1 {
+            break; // every suffix has a distinct rank
+        }
+        k *= 2;
+    }
+    sa
+}
+
+// SA-IS over the alphabet `0..=upper`
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return vec![],
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    // ls[i] is true when suffix i is S-type, i.e. smaller than suffix i + 1
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] { ls[i + 1] } else { s[i] < s[i + 1] };
+    }
+    let mut sum_l = vec![0usize; upper + 1];
+    let mut sum_s = vec![0usize; upper + 1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[s[i]] += 1;
+        } else {
+            sum_l[s[i] + 1] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+    let induce = |lms: &[usize], sa: &mut [usize]| {
+        for slot in sa.iter_mut() {
+            *slot = usize::MAX;
+        }
+        let mut buf = sum_s.clone();
+        for &d in lms {
+            if d == n {
+                continue;
+            }
+            sa[buf[s[d]]] = d;
+            buf[s[d]] += 1;
+        }
+        buf.copy_from_slice(&sum_l);
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != usize::MAX && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != usize::MAX && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+    };
+    let mut lms_map = vec![usize::MAX; n + 1];
+    let mut lms = Vec::new();
+    for i in 1..n {
+        if !ls[i - 1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let m = lms.len();
+    let mut sa = vec![usize::MAX; n];
+    induce(&lms, &mut sa);
+    if m != 0 {
+        let mut sorted_lms: Vec<usize> = sa.iter().cloned().filter(|&v| lms_map[v] != usize::MAX).collect();
+        let mut rec_s = vec![0usize; m];
+        let mut rec_upper = 0;
+        rec_s[lms_map[sorted_lms[0]]] = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = if lms_map[l] + 1 < m { lms[lms_map[l] + 1] } else { n };
+            let end_r = if lms_map[r] + 1 < m { lms[lms_map[r] + 1] } else { n };
+            let same = if end_l - l != end_r - r {
+                false
+            } else {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                l != n && s[l] == s[r]
+            };
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = sa_is(&rec_s, rec_upper);
+        for i in 0..m {
+            sorted_lms[i] = lms[rec_sa[i]];
+        }
+        induce(&sorted_lms, &mut sa);
+    }
+    sa
+}
+
+// `lcp[i]` is the length of the common prefix of suffixes `sa[i - 1]` and `sa[i]`, `lcp[0]` is 0
+pub fn kasai_lcp(text: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    assert_eq!(sa.len(), n, "Suffix array does not match the text");
+    let mut rank = vec![0usize; n];
+    for (i, &pos) in sa.iter().enumerate() {
+        rank[pos] = i;
+    }
+    let mut lcp = vec![0usize; n];
+    let mut h = 0;
+    for i in 0..n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+// Range of `sa` whose suffixes start with `pattern`
+pub fn find_range(text: &[u8], sa: &[usize], pattern: &[u8]) -> Range<usize> {
+    let prefix = |i: usize| &text[i..(i + pattern.len()).min(text.len())];
+    let start = sa.partition_point(|&i| prefix(i) < pattern);
+    let end = start + sa[start..].partition_point(|&i| prefix(i) == pattern);
+    start..end
+}
+
+// Every position where `pattern` occurs in `text`, in increasing order
+pub fn find_all(text: &[u8], sa: &[usize], pattern: &[u8]) -> Vec<usize> {
+    let mut ans = sa[find_range(text, sa, pattern)].to_vec();
+    ans.sort_unstable();
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    fn naive_suffix_array(text: &[u8]) -> Vec<usize> {
+        let mut sa: Vec<usize> = (0..text.len()).collect();
+        sa.sort_by(|&a, &b| text[a..].cmp(&text[b..]));
+        sa
+    }
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut ans: Vec<Vec<u8>> = ["", "a", "ab", "ba", "banana", "mississippi", "abracadabra", "aaaaaaaaaa"]
+            .iter().map(|s| s.as_bytes().to_vec()).collect();
+        let mut seed = 0x2545_f491u32;
+        for len in [50, 333, 1000].iter() {
+            for alphabet in [2u32, 4, 256].iter() {
+                ans.push((0..*len).map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    ((seed >> 16) % alphabet) as u8
+                }).collect());
+            }
+        }
+        ans
+    }
+
+    #[test]
+    fn suffix_array() {
+        for text in samples() {
+            assert_eq!(super::suffix_array(&text), naive_suffix_array(&text));
+        }
+        assert_eq!(super::suffix_array(b"banana"), vec![5, 3, 1, 0, 4, 2]);
+    }
+
+    #[test]
+    fn suffix_array_doubling() {
+        for text in samples() {
+            assert_eq!(super::suffix_array_doubling(&text), naive_suffix_array(&text));
+        }
+    }
+
+    #[test]
+    fn kasai_lcp() {
+        let sa = super::suffix_array(b"banana");
+        assert_eq!(super::kasai_lcp(b"banana", &sa), vec![0, 1, 3, 0, 0, 2]);
+        for text in samples() {
+            let sa = super::suffix_array(&text);
+            let suffixes: Vec<&[u8]> = sa.iter().map(|&i| &text[i..]).collect();
+            assert_eq!(super::kasai_lcp(&text, &sa), crate::sort::lcp_array(&suffixes));
+        }
+    }
+
+    #[test]
+    fn find() {
+        let text = b"abracadabra";
+        let sa = super::suffix_array(text);
+        assert_eq!(super::find_all(text, &sa, b"abra"), vec![0, 7]);
+        assert_eq!(super::find_all(text, &sa, b"a"), vec![0, 3, 5, 7, 10]);
+        assert_eq!(super::find_all(text, &sa, b"cad"), vec![4]);
+        assert_eq!(super::find_all(text, &sa, b"abracadabra!"), vec![]);
+        assert_eq!(super::find_all(text, &sa, b"dab"), vec![6]);
+        assert_eq!(super::find_range(text, &sa, b"x"), 11..11);
+        assert_eq!(super::find_all(text, &sa, b"").len(), 11);
+    }
+}